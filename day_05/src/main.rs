@@ -0,0 +1,190 @@
+mod manual;
+use std::fs;
+
+use manual::Manual;
+
+struct Options {
+    input_filename: String,
+    report: bool,
+}
+
+impl Options {
+    fn from_args(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut report = false;
+        for arg in args {
+            match arg.as_str() {
+                "--report" => report = true,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            report,
+        })
+    }
+}
+
+fn get_result_part1(manual: &Manual) -> u32 {
+    manual
+        .updates
+        .iter()
+        .filter(|update| manual.is_ordered(update))
+        .map(|update| update[update.len() / 2])
+        .sum()
+}
+
+fn get_result_part2(manual: &Manual) -> Result<u32, String> {
+    let mut result = 0;
+    for (i, update) in manual.updates.iter().enumerate() {
+        if manual.is_ordered(update) {
+            continue;
+        }
+        let sorted = manual
+            .sort_update(update)
+            .map_err(|cycle| format!("Update {} cannot be ordered: {}", i + 1, cycle))?;
+        result += sorted[sorted.len() / 2];
+    }
+    Ok(result)
+}
+
+fn get_report(manual: &Manual) -> Vec<String> {
+    let mut report = vec![];
+    match manual.find_cycle(&manual.get_pages()) {
+        Some(cycle) => report.push(format!("The rules have a cycle: {}", cycle)),
+        None => report.push("The rules have no cycles".to_string()),
+    }
+    for (i, update) in manual.updates.iter().enumerate() {
+        let violated = manual.get_violated_rules(update);
+        if violated.is_empty() {
+            continue;
+        }
+        let rules: Vec<String> = violated.iter().map(|rule| rule.to_string()).collect();
+        report.push(format!("Update {} violates {}", i + 1, rules.join(", ")));
+    }
+    report
+}
+
+fn main() {
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: day_05 <input> [--report]");
+        std::process::exit(1)
+    });
+
+    let manual_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
+    let manual = Manual::new(&manual_string);
+
+    if options.report {
+        for line in get_report(&manual) {
+            println!("{}", line);
+        }
+    }
+
+    let result_part1 = get_result_part1(&manual);
+    println!("Result part 1: {}", result_part1);
+
+    match get_result_part2(&manual) {
+        Ok(result_part2) => println!("Result part 2: {}", result_part2),
+        Err(message) => println!("Result part 2: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use manual::{Cycle, Rule};
+
+    fn read_example() -> Manual {
+        let manual_string =
+            fs::read_to_string("test.txt").expect("Should have been able to read the file");
+        Manual::new(&manual_string)
+    }
+
+    #[test]
+    fn options() {
+        let args = ["--report", "input.txt"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "input.txt");
+        assert!(options.report);
+        assert!(Options::from_args(std::iter::empty()).is_err());
+    }
+
+    #[test]
+    fn example() {
+        let manual = read_example();
+        assert_eq!(get_result_part1(&manual), 143);
+        assert_eq!(get_result_part2(&manual), Ok(123));
+    }
+
+    #[test]
+    fn sort_updates() {
+        let manual = read_example();
+        assert_eq!(
+            manual.sort_update(&[75, 97, 47, 61, 53]),
+            Ok(vec![97, 75, 47, 61, 53])
+        );
+        assert_eq!(manual.sort_update(&[61, 13, 29]), Ok(vec![61, 29, 13]));
+        assert_eq!(
+            manual.sort_update(&[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+    }
+
+    #[test]
+    fn violated_rules() {
+        let manual = read_example();
+        assert_eq!(
+            manual.get_violated_rules(&[61, 13, 29]),
+            vec![Rule {
+                predecessor: 29,
+                successor: 13
+            }]
+        );
+        assert!(manual.get_violated_rules(&[75, 47, 61, 53, 29]).is_empty());
+        let report = get_report(&manual);
+        assert_eq!(
+            report,
+            vec![
+                "The rules have no cycles",
+                "Update 4 violates 97|75",
+                "Update 5 violates 29|13",
+                "Update 6 violates 75|13, 29|13, 47|13, 47|29",
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let manual = Manual::new("1|2\n2|3\n3|1\n3|4\n\n1,2,4\n4,3,2,1\n");
+        assert_eq!(manual.updates.len(), 2);
+        assert_eq!(manual.sort_update(&[4, 2, 1]), Ok(vec![4, 1, 2]));
+        let cycle = manual.sort_update(&[4, 3, 2, 1]).unwrap_err();
+        assert_eq!(
+            cycle,
+            Cycle {
+                pages: vec![1, 2, 3]
+            }
+        );
+        assert_eq!(cycle.to_string(), "1 -> 2 -> 3 -> 1");
+        assert_eq!(
+            get_result_part2(&manual),
+            Err("Update 2 cannot be ordered: 1 -> 2 -> 3 -> 1".to_string())
+        );
+        assert!(manual.find_cycle(&[1, 2, 4]).is_none());
+
+        let manual = Manual::new("5|5\n\n5,6\n");
+        assert_eq!(manual.sort_update(&[5, 6]), Err(Cycle { pages: vec![5] }));
+    }
+
+    #[test]
+    fn repeated_pages() {
+        let manual = Manual::new("1|2\n\n2,1,2\n");
+        assert_eq!(manual.sort_update(&[2, 1, 2]), Ok(vec![1, 2, 2]));
+        assert_eq!(get_result_part2(&manual), Ok(2));
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct Rule {
+    pub predecessor: u32,
+    pub successor: u32,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}|{}", self.predecessor, self.successor)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct Cycle {
+    pub pages: Vec<u32>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pages: Vec<String> = self
+            .pages
+            .iter()
+            .chain(self.pages.first())
+            .map(|page| page.to_string())
+            .collect();
+        write!(f, "{}", pages.join(" -> "))
+    }
+}
+
+#[derive(Eq, PartialEq, Default, Debug)]
+pub struct Manual {
+    next_pages: HashMap<u32, HashSet<u32>>,
+    pub updates: Vec<Vec<u32>>,
+}
+
+impl Manual {
+    pub fn new(manual_string: &str) -> Self {
+        let (raw_rules, raw_updates) = manual_string
+            .split_once("\n\n")
+            .expect("The manual should have rules and updates separated by an empty line");
+
+        let mut manual = Manual::default();
+        for line in raw_rules.lines() {
+            let (predecessor, successor) = line.split_once('|').expect("Invalid rule");
+            manual.add(predecessor.parse().unwrap(), successor.parse().unwrap());
+        }
+
+        manual.updates = raw_updates
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.split(',')
+                    .map(|page| page.parse::<u32>().unwrap())
+                    .collect::<Vec<u32>>()
+            })
+            .collect();
+        manual
+    }
+
+    pub fn add(&mut self, predecessor: u32, successor: u32) {
+        self.next_pages
+            .entry(predecessor)
+            .or_default()
+            .insert(successor);
+    }
+
+    pub fn are_ordered(&self, predecessor: &u32, posible_successor: &u32) -> bool {
+        self.next_pages
+            .get(predecessor)
+            .is_some_and(|next_pages| next_pages.contains(posible_successor))
+    }
+
+    pub fn get_pages(&self) -> Vec<u32> {
+        let mut pages: Vec<u32> = self
+            .next_pages
+            .iter()
+            .flat_map(|(&page, next_pages)| next_pages.iter().copied().chain([page]))
+            .collect::<HashSet<u32>>()
+            .into_iter()
+            .collect();
+        pages.sort();
+        pages
+    }
+
+    // Rules whose successor is printed before their predecessor.
+    pub fn get_violated_rules(&self, update: &[u32]) -> Vec<Rule> {
+        let mut violated = vec![];
+        for (i, &successor) in update.iter().enumerate() {
+            for &predecessor in update[i + 1..].iter() {
+                if self.are_ordered(&predecessor, &successor) {
+                    violated.push(Rule {
+                        predecessor,
+                        successor,
+                    });
+                }
+            }
+        }
+        violated
+    }
+
+    pub fn is_ordered(&self, update: &[u32]) -> bool {
+        self.get_violated_rules(update).is_empty()
+    }
+
+    // Orders the pages using only the rules between pages of the update.
+    // Among the pages that are ready, the one printed first goes first, so
+    // the result is deterministic even when the rules allow several orders.
+    // Incoming rules are counted per position, so a page printed twice is
+    // placed twice.
+    pub fn sort_update(&self, update: &[u32]) -> Result<Vec<u32>, Cycle> {
+        let mut incoming: Vec<usize> = update
+            .iter()
+            .map(|successor| {
+                update
+                    .iter()
+                    .filter(|predecessor| self.are_ordered(predecessor, successor))
+                    .count()
+            })
+            .collect();
+
+        let mut sorted = Vec::with_capacity(update.len());
+        let mut placed = vec![false; update.len()];
+        while sorted.len() < update.len() {
+            let Some(position) = (0..update.len()).find(|&i| !placed[i] && incoming[i] == 0) else {
+                return Err(self.get_unplaced_cycle(update, &placed));
+            };
+            placed[position] = true;
+            sorted.push(update[position]);
+            for (i, successor) in update.iter().enumerate() {
+                if self.are_ordered(&update[position], successor) {
+                    incoming[i] -= 1;
+                }
+            }
+        }
+        Ok(sorted)
+    }
+
+    // Every page left unplaced by the sort still has an unplaced
+    // predecessor, so walking back through predecessors must close a loop.
+    fn get_unplaced_cycle(&self, update: &[u32], placed: &[bool]) -> Cycle {
+        let mut current = placed.iter().position(|&placed| !placed).unwrap();
+        let mut path = vec![];
+        while !path.contains(&current) {
+            path.push(current);
+            current = (0..update.len())
+                .find(|&i| !placed[i] && self.are_ordered(&update[i], &update[current]))
+                .unwrap();
+        }
+        let cycle_start = path.iter().position(|&i| i == current).unwrap();
+        Cycle {
+            pages: path[cycle_start..]
+                .iter()
+                .rev()
+                .map(|&i| update[i])
+                .collect(),
+        }
+    }
+
+    // Depth-first search over the rules between the given pages, returning
+    // the pages of the first cycle found.
+    pub fn find_cycle(&self, pages: &[u32]) -> Option<Cycle> {
+        let page_set: HashSet<u32> = pages.iter().copied().collect();
+        let mut finished = HashSet::new();
+        for &start in pages {
+            if finished.contains(&start) {
+                continue;
+            }
+            let mut path = vec![start];
+            let mut on_path = HashSet::from([start]);
+            let mut stack = vec![self.get_successors(start, &page_set)];
+            while let Some(successors) = stack.last_mut() {
+                match successors.pop() {
+                    Some(next) if on_path.contains(&next) => {
+                        let cycle_start = path.iter().position(|&page| page == next).unwrap();
+                        return Some(Cycle {
+                            pages: path[cycle_start..].to_vec(),
+                        });
+                    }
+                    Some(next) if !finished.contains(&next) => {
+                        path.push(next);
+                        on_path.insert(next);
+                        stack.push(self.get_successors(next, &page_set));
+                    }
+                    Some(_) => {}
+                    None => {
+                        let page = path.pop().unwrap();
+                        on_path.remove(&page);
+                        finished.insert(page);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn get_successors(&self, page: u32, pages: &HashSet<u32>) -> Vec<u32> {
+        let mut successors: Vec<u32> = self
+            .next_pages
+            .get(&page)
+            .map(|next_pages| next_pages.intersection(pages).copied().collect())
+            .unwrap_or_default();
+        successors.sort_by(|a, b| b.cmp(a));
+        successors
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operator {
+    Add,
+    Multiply,
+    Concatenate,
+    Subtract,
+    Divide,
+    Xor,
+    Power,
+}
+
+pub const PART1_OPERATORS: [Operator; 2] = [Operator::Add, Operator::Multiply];
+pub const PART2_OPERATORS: [Operator; 3] =
+    [Operator::Add, Operator::Multiply, Operator::Concatenate];
+
+// Ranges of predecessors at most this wide are still searched backwards,
+// wider ones by evaluating the operands before them forwards.
+const MAX_BACKWARD_RANGE: u128 = 16;
+
+// The left operands that give a result when combined with a right operand.
+#[derive(PartialEq, Eq, Debug)]
+enum Predecessors {
+    Values(Vec<u128>),
+    Range(u128, u128),
+}
+
+impl Operator {
+    pub fn new(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(Operator::Add),
+            "*" => Some(Operator::Multiply),
+            "||" => Some(Operator::Concatenate),
+            "-" => Some(Operator::Subtract),
+            "/" => Some(Operator::Divide),
+            "^" => Some(Operator::Xor),
+            "**" => Some(Operator::Power),
+            _ => None,
+        }
+    }
+
+    // Returns None when the result is negative, undefined or does not fit
+    // in a u128.
+    pub fn apply(&self, left: u128, right: u128) -> Option<u128> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Concatenate => left
+                .checked_mul(get_digits_power(right)?)?
+                .checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Divide => left.checked_div(right),
+            Operator::Xor => Some(left ^ right),
+            Operator::Power => left.checked_pow(u32::try_from(right).ok()?),
+        }
+    }
+
+    fn undo(&self, result: u128, right: u128) -> Predecessors {
+        let values = match self {
+            Operator::Add => result.checked_sub(right).into_iter().collect(),
+            Operator::Multiply if right == 0 => {
+                return if result == 0 {
+                    Predecessors::Range(0, u128::MAX)
+                } else {
+                    Predecessors::Values(vec![])
+                }
+            }
+            Operator::Multiply => result
+                .is_multiple_of(right)
+                .then_some(result / right)
+                .into_iter()
+                .collect(),
+            Operator::Concatenate => get_digits_power(right)
+                .filter(|&power| result % power == right)
+                .map(|power| result / power)
+                .into_iter()
+                .collect(),
+            Operator::Subtract => result.checked_add(right).into_iter().collect(),
+            Operator::Divide if right == 0 => vec![],
+            Operator::Divide => {
+                return match result.checked_mul(right) {
+                    Some(low) => Predecessors::Range(low, low.saturating_add(right - 1)),
+                    None => Predecessors::Values(vec![]),
+                }
+            }
+            Operator::Xor => vec![result ^ right],
+            Operator::Power if right == 0 => {
+                return if result == 1 {
+                    Predecessors::Range(0, u128::MAX)
+                } else {
+                    Predecessors::Values(vec![])
+                }
+            }
+            Operator::Power => get_integer_root(result, right).into_iter().collect(),
+        };
+        Predecessors::Values(values)
+    }
+}
+
+fn get_digits_power(value: u128) -> Option<u128> {
+    10_u128.checked_pow(value.checked_ilog10().unwrap_or(0) + 1)
+}
+
+fn get_integer_root(value: u128, exponent: u128) -> Option<u128> {
+    let exponent = u32::try_from(exponent).ok()?;
+    // Smallest root whose power reaches the value.
+    let (mut low, mut high) = (0, value);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if middle
+            .checked_pow(exponent)
+            .is_none_or(|power| power >= value)
+        {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    (low.checked_pow(exponent) == Some(value)).then_some(low)
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Multiply => write!(f, "*"),
+            Operator::Concatenate => write!(f, "||"),
+            Operator::Subtract => write!(f, "-"),
+            Operator::Divide => write!(f, "/"),
+            Operator::Xor => write!(f, "^"),
+            Operator::Power => write!(f, "**"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Solution {
+    pub operators: Vec<Operator>,
+    pub count: u128,
+}
+
+pub struct CalibrationEquiation {
+    pub result: u128,
+    pub operands: Vec<u128>,
+}
+
+impl CalibrationEquiation {
+    pub fn new(line: &str) -> Self {
+        let mut iter_line = line.split(':');
+        let result = iter_line.next().unwrap().parse::<u128>().unwrap();
+        let operands = iter_line
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .map(|operand| operand.parse::<u128>().unwrap())
+            .collect();
+
+        CalibrationEquiation { result, operands }
+    }
+
+    // Works from the last operand back to the first, undoing each operator
+    // on the target, so that only operators compatible with the target are
+    // ever explored. When a wide range of left operands would do, as for
+    // `* 0` or a large divisor, the remaining operators are enumerated
+    // forwards instead.
+    pub fn solve(&self, operators: &[Operator]) -> Option<Solution> {
+        let last = self.operands.len().checked_sub(1)?;
+        let mut assignment = vec![];
+        let mut first = None;
+        let count = self.count_solutions(operators, self.result, last, &mut assignment, &mut first);
+        first.map(|mut operators| {
+            operators.reverse();
+            Solution { operators, count }
+        })
+    }
+
+    fn count_solutions(
+        &self,
+        operators: &[Operator],
+        target: u128,
+        index: usize,
+        assignment: &mut Vec<Operator>,
+        first: &mut Option<Vec<Operator>>,
+    ) -> u128 {
+        if index == 0 {
+            let found = target == self.operands[0];
+            if found && first.is_none() {
+                *first = Some(assignment.clone());
+            }
+            return found as u128;
+        }
+        let mut count = 0;
+        for &operator in operators {
+            assignment.push(operator);
+            let values = match operator.undo(target, self.operands[index]) {
+                Predecessors::Values(values) => values,
+                Predecessors::Range(low, high) if high - low < MAX_BACKWARD_RANGE => {
+                    (low..=high).collect()
+                }
+                Predecessors::Range(low, high) => {
+                    let mut prefix = vec![];
+                    count += self.count_in_range(
+                        operators,
+                        self.operands[0],
+                        1,
+                        (index, low, high),
+                        &mut prefix,
+                        assignment,
+                        first,
+                    );
+                    vec![]
+                }
+            };
+            for previous in values {
+                count += self.count_solutions(operators, previous, index - 1, assignment, first);
+            }
+            assignment.pop();
+        }
+        count
+    }
+
+    // Counts the assignments of the operators before `end` whose evaluation
+    // does not fail and falls between `low` and `high`.
+    #[allow(clippy::too_many_arguments)]
+    fn count_in_range(
+        &self,
+        operators: &[Operator],
+        value: u128,
+        index: usize,
+        (end, low, high): (usize, u128, u128),
+        prefix: &mut Vec<Operator>,
+        assignment: &[Operator],
+        first: &mut Option<Vec<Operator>>,
+    ) -> u128 {
+        if index == end {
+            if !(low..=high).contains(&value) {
+                return 0;
+            }
+            if first.is_none() {
+                *first = Some(
+                    assignment
+                        .iter()
+                        .chain(prefix.iter().rev())
+                        .copied()
+                        .collect(),
+                );
+            }
+            return 1;
+        }
+        let mut count = 0;
+        for &operator in operators {
+            if let Some(value) = operator.apply(value, self.operands[index]) {
+                prefix.push(operator);
+                count += self.count_in_range(
+                    operators,
+                    value,
+                    index + 1,
+                    (end, low, high),
+                    prefix,
+                    assignment,
+                    first,
+                );
+                prefix.pop();
+            }
+        }
+        count
+    }
+
+    pub fn format_expression(&self, operators: &[Operator]) -> String {
+        let mut expression = self.operands[0].to_string();
+        for (operator, operand) in operators.iter().zip(self.operands.iter().skip(1)) {
+            expression.push_str(&format!(" {} {}", operator, operand));
+        }
+        expression
+    }
+
+    pub fn get_partial_result(&self, operators: &[Operator]) -> u128 {
+        match self.solve(operators) {
+            Some(_) => self.result,
+            None => 0,
+        }
+    }
+}
@@ -1,79 +1,103 @@
-use itertools::{repeat_n, Itertools};
+mod equation;
 use std::fs;
 
-struct CalibrationEquiation {
-    result: u64,
-    operands: Vec<u64>,
-    operators: Vec<fn(u64, u64) -> u64>,
+use equation::{CalibrationEquiation, Operator, PART1_OPERATORS, PART2_OPERATORS};
+
+struct Options {
+    input_filename: String,
+    operators: Option<Vec<Operator>>,
+    verbose: bool,
 }
 
-impl CalibrationEquiation {
-    pub fn new(line: &str) -> Self {
-        let mut iter_line = line.split(": ");
-        let result = iter_line.next().unwrap().parse::<u64>().unwrap();
-        let operands = iter_line
-            .next()
-            .unwrap()
-            .split_whitespace()
-            .map(|operand| operand.parse::<u64>().unwrap())
-            .collect();
-        let operators = vec![
-            |a: u64, b: u64| a + b,
-            |a: u64, b: u64| a * b,
-            |a: u64, b: u64| (a.to_string() + &b.to_string()).parse::<u64>().unwrap(),
-        ];
-
-        CalibrationEquiation {
-            result,
-            operands,
-            operators,
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut operators = None;
+        let mut verbose = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--ops" => {
+                    operators = Some(
+                        args.next()
+                            .and_then(|symbols| {
+                                symbols
+                                    .split(',')
+                                    .map(Operator::new)
+                                    .collect::<Option<Vec<Operator>>>()
+                            })
+                            .ok_or("Option --ops expects a comma separated list of +, *, ||, -, /, ^ and **")?,
+                    )
+                }
+                "--verbose" => verbose = true,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
         }
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            operators,
+            verbose,
+        })
     }
+}
 
-    fn eval_combination(&self, operator_combination: Vec<fn(u64, u64) -> u64>) -> Option<u64> {
-        self.operands
-            .clone()
-            .into_iter()
-            .skip(1)
-            .enumerate()
-            .try_fold(self.operands[0], |acc, (j, operand)| {
-                let current_result = operator_combination[j](acc, operand);
-
-                if current_result <= self.result {
-                    Some(current_result)
-                } else {
-                    None
-                }
-            })
-            .filter(|x| *x == self.result)
-    }
+fn read_equations(calibration_string: &str) -> Vec<CalibrationEquiation> {
+    calibration_string
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(CalibrationEquiation::new)
+        .collect()
+}
 
-    pub fn get_partial_result(&self) -> u64 {
-        repeat_n(self.operators.clone().into_iter(), self.operands.len())
-            .multi_cartesian_product()
-            .find_map(|operator_combination| self.eval_combination(operator_combination))
-            .unwrap_or(0)
+fn print_solution(calibration_eq: &CalibrationEquiation, operators: &[Operator]) {
+    match calibration_eq.solve(operators) {
+        Some(solution) => println!(
+            "{} = {} ({} {})",
+            calibration_eq.result,
+            calibration_eq.format_expression(&solution.operators),
+            solution.count,
+            if solution.count == 1 { "way" } else { "ways" }
+        ),
+        None => println!("{} has no solution", calibration_eq.result),
     }
 }
 
-fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+fn get_result(equations: &[CalibrationEquiation], operators: &[Operator], verbose: bool) -> u128 {
+    equations
+        .iter()
+        .map(|calibration_eq| {
+            if verbose {
+                print_solution(calibration_eq, operators);
+            }
+            calibration_eq.get_partial_result(operators)
+        })
+        .sum()
+}
 
+fn main() {
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: day_07 <input> [--ops +,*,||] [--verbose]");
         std::process::exit(1)
     });
 
-    let calibration_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let calibration_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
+    let equations = read_equations(&calibration_string);
 
-    let result: u64 = calibration_string
-        .split('\n')
-        .map(|line| {
-            let calibration_eq = CalibrationEquiation::new(&line);
-            calibration_eq.get_partial_result()
-        })
-        .sum();
-    println!("\n{result:?}");
+    if let Some(operators) = options.operators {
+        let result = get_result(&equations, &operators, options.verbose);
+        println!("Result: {}", result);
+        return;
+    }
+
+    let result_part1 = get_result(&equations, &PART1_OPERATORS, options.verbose);
+    println!("Result part 1: {}", result_part1);
+
+    let result_part2 = get_result(&equations, &PART2_OPERATORS, options.verbose);
+    println!("Result part 2: {}", result_part2);
 }
 
 #[cfg(test)]
@@ -83,48 +107,253 @@ mod tests {
     #[test]
     fn sum() {
         let calibration_eq = CalibrationEquiation::new("29: 10 19");
-        assert_eq!(calibration_eq.get_partial_result(), 29);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 29);
     }
 
     #[test]
     fn mult() {
         let calibration_eq = CalibrationEquiation::new("190: 10 19");
-        assert_eq!(calibration_eq.get_partial_result(), 190);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 190);
     }
 
     #[test]
     fn sum_and_mult() {
         let calibration_eq = CalibrationEquiation::new("3267: 81 40 27");
-        assert_eq!(calibration_eq.get_partial_result(), 3267);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 3267);
     }
 
     #[test]
     fn complex_sum_and_mult() {
         let calibration_eq = CalibrationEquiation::new("292: 11 6 16 20");
-        assert_eq!(calibration_eq.get_partial_result(), 292);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 292);
     }
 
     #[test]
     fn no_operations_for_this() {
         let calibration_eq = CalibrationEquiation::new("21037: 9 7 18 13");
-        assert_eq!(calibration_eq.get_partial_result(), 0);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 0);
     }
 
     #[test]
     fn concatenation() {
         let calibration_eq = CalibrationEquiation::new("156: 15 6");
-        assert_eq!(calibration_eq.get_partial_result(), 156);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 156);
     }
 
     #[test]
     fn concatenation_and_sum() {
         let calibration_eq = CalibrationEquiation::new("192: 17 8 14");
-        assert_eq!(calibration_eq.get_partial_result(), 192);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 192);
     }
 
     #[test]
     fn all_operations() {
         let calibration_eq = CalibrationEquiation::new("7290: 6 8 6 15");
-        assert_eq!(calibration_eq.get_partial_result(), 7290);
+        assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 7290);
+    }
+
+    #[test]
+    fn expression_and_count() {
+        let calibration_eq = CalibrationEquiation::new("3267: 81 40 27");
+        let solution = calibration_eq.solve(&PART1_OPERATORS).unwrap();
+        assert_eq!(solution.count, 2);
+        assert_eq!(
+            calibration_eq.format_expression(&solution.operators),
+            "81 * 40 + 27"
+        );
+        let calibration_eq = CalibrationEquiation::new("7290: 6 8 6 15");
+        let solution = calibration_eq.solve(&PART2_OPERATORS).unwrap();
+        assert_eq!(
+            calibration_eq.format_expression(&solution.operators),
+            "6 * 8 || 6 * 15"
+        );
+        assert_eq!(calibration_eq.solve(&PART1_OPERATORS), None);
+    }
+
+    #[test]
+    fn concatenation_suffix() {
+        assert_eq!(
+            CalibrationEquiation::new("1010: 10 10").get_partial_result(&PART2_OPERATORS),
+            1010
+        );
+        assert_eq!(
+            CalibrationEquiation::new("110: 1 10").get_partial_result(&PART2_OPERATORS),
+            110
+        );
+        assert_eq!(
+            CalibrationEquiation::new("210: 1 10").get_partial_result(&PART2_OPERATORS),
+            0
+        );
+    }
+
+    #[test]
+    fn example() {
+        let calibration_string =
+            fs::read_to_string("test.txt").expect("Should have been able to read the file");
+        let equations = read_equations(&calibration_string);
+        assert_eq!(get_result(&equations, &PART1_OPERATORS, false), 3749);
+        assert_eq!(get_result(&equations, &PART2_OPERATORS, false), 11387);
+    }
+
+    fn count_forwards(calibration_eq: &CalibrationEquiation, operators: &[Operator]) -> u128 {
+        let mut values = vec![Some(calibration_eq.operands[0])];
+        for &operand in calibration_eq.operands.iter().skip(1) {
+            values = values
+                .into_iter()
+                .flat_map(|value| {
+                    operators
+                        .iter()
+                        .map(move |operator| value.and_then(|value| operator.apply(value, operand)))
+                })
+                .collect();
+        }
+        values
+            .into_iter()
+            .filter(|&value| value == Some(calibration_eq.result))
+            .count() as u128
+    }
+
+    #[test]
+    fn operators() {
+        let symbols = ["+", "*", "||", "-", "/", "^", "**"];
+        let operators: Vec<Operator> = symbols
+            .iter()
+            .map(|symbol| Operator::new(symbol).unwrap())
+            .collect();
+        let results: Vec<Option<u128>> = operators
+            .iter()
+            .map(|operator| operator.apply(12, 3))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                Some(15),
+                Some(36),
+                Some(123),
+                Some(9),
+                Some(4),
+                Some(15),
+                Some(1728)
+            ]
+        );
+        for (operator, symbol) in operators.iter().zip(symbols) {
+            assert_eq!(operator.to_string(), symbol);
+        }
+        assert_eq!(Operator::Subtract.apply(3, 12), None);
+        assert_eq!(Operator::Divide.apply(3, 0), None);
+        assert_eq!(Operator::Multiply.apply(u128::MAX, 2), None);
+        assert_eq!(Operator::Concatenate.apply(u128::MAX / 10, 6), None);
+        assert_eq!(Operator::Power.apply(2, 128), None);
+        assert_eq!(Operator::new("%"), None);
+    }
+
+    #[test]
+    fn backward_solver_matches_forward_enumeration() {
+        let all_operators: Vec<Operator> = ["+", "*", "||", "-", "/", "^", "**"]
+            .iter()
+            .map(|symbol| Operator::new(symbol).unwrap())
+            .collect();
+        for line in [
+            // Multiplying or raising to zero accepts any left operand.
+            "0: 3 0 5",
+            "1: 7 0",
+            "1: 2 3 0 1",
+            "10: 4 6 0 10",
+            "0: 0 0 0 0",
+            // Division accepts a range of left operands.
+            "3: 7 2",
+            "2: 9 2 2",
+            // Subtraction, xor and concatenation.
+            "4: 9 5",
+            "6: 5 3",
+            "12: 1 2",
+            "5: 11 2 0 5",
+            "24: 2 3 4",
+            "100: 10 10",
+            "81: 3 1 4",
+        ] {
+            let calibration_eq = CalibrationEquiation::new(line);
+            let expected = count_forwards(&calibration_eq, &all_operators);
+            let solution = calibration_eq.solve(&all_operators);
+            assert_eq!(
+                solution.as_ref().map_or(0, |solution| solution.count),
+                expected,
+                "{}",
+                line
+            );
+            if let Some(solution) = solution {
+                let value = solution
+                    .operators
+                    .iter()
+                    .zip(calibration_eq.operands.iter().skip(1))
+                    .try_fold(calibration_eq.operands[0], |value, (operator, &operand)| {
+                        operator.apply(value, operand)
+                    });
+                assert_eq!(value, Some(calibration_eq.result));
+            }
+        }
+    }
+
+    #[test]
+    fn missing_operands() {
+        for line in ["5: ", "5:"] {
+            let calibration_eq = CalibrationEquiation::new(line);
+            assert!(calibration_eq.operands.is_empty());
+            assert_eq!(calibration_eq.solve(&PART2_OPERATORS), None);
+            assert_eq!(calibration_eq.get_partial_result(&PART2_OPERATORS), 0);
+        }
+    }
+
+    #[test]
+    fn large_divisors() {
+        let divide = [Operator::Divide];
+        assert_eq!(
+            CalibrationEquiation::new("2: 10 100000000000").solve(&divide),
+            None
+        );
+        let solution = CalibrationEquiation::new("0: 10 100000000000")
+            .solve(&divide)
+            .unwrap();
+        assert_eq!(solution.count, 1);
+
+        let operators = [Operator::Add, Operator::Divide, Operator::Multiply];
+        for line in [
+            "1: 300000000000 2 100000000000",
+            "3: 100000000000 200000000000 100000000000",
+            "7: 2 3 1 1000 1000",
+        ] {
+            let calibration_eq = CalibrationEquiation::new(line);
+            let solution = calibration_eq.solve(&operators).unwrap();
+            assert_eq!(
+                solution.count,
+                count_forwards(&calibration_eq, &operators),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn custom_operators() {
+        let calibration_eq = CalibrationEquiation::new("1: 3 3 2 0");
+        let solution = calibration_eq
+            .solve(&[Operator::Subtract, Operator::Power])
+            .unwrap();
+        assert_eq!(
+            calibration_eq.format_expression(&solution.operators),
+            "3 - 3 ** 2 ** 0"
+        );
+        let args = ["test.txt", "--ops", "+,*,||,**"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(
+            options.operators,
+            Some(vec![
+                Operator::Add,
+                Operator::Multiply,
+                Operator::Concatenate,
+                Operator::Power
+            ])
+        );
+        assert!(Options::from_args(["--ops", "+,%"].map(String::from).into_iter()).is_err());
     }
 }
@@ -0,0 +1,327 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+const MAX_SPAN_LENGTH: usize = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct File {
+    pub id: usize,
+    pub start: usize,
+    pub length: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Gap {
+    pub start: usize,
+    pub length: usize,
+}
+
+// The files on disk sorted by position. A file split by the block-wise
+// compaction appears once per fragment.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Layout {
+    pub files: Vec<File>,
+    pub size: usize,
+}
+
+impl Layout {
+    fn new(mut files: Vec<File>, size: usize) -> Self {
+        files.retain(|file| file.length > 0);
+        files.sort_by_key(|file| file.start);
+        // Fragments of the same file that ended up next to each other are
+        // merged, so that equal layouts compare equal.
+        let mut merged: Vec<File> = vec![];
+        for file in files {
+            match merged.last_mut() {
+                Some(last) if last.id == file.id && last.start + last.length == file.start => {
+                    last.length += file.length
+                }
+                _ => merged.push(file),
+            }
+        }
+        Layout {
+            files: merged,
+            size,
+        }
+    }
+
+    fn from_blocks(blocks: &[Option<usize>]) -> Self {
+        let files = blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(position, block)| {
+                block.map(|id| File {
+                    id,
+                    start: position,
+                    length: 1,
+                })
+            })
+            .collect();
+        Layout::new(files, blocks.len())
+    }
+
+    fn get_blocks(&self) -> Vec<Option<usize>> {
+        let mut blocks = vec![None; self.size];
+        for file in self.files.iter() {
+            blocks[file.start..file.start + file.length].fill(Some(file.id));
+        }
+        blocks
+    }
+
+    // Runs of blocks as (id, length) pairs, with None for free space.
+    fn get_runs(&self) -> Vec<(Option<usize>, usize)> {
+        let mut runs = vec![];
+        let mut position = 0;
+        for file in self.files.iter() {
+            if file.start > position {
+                runs.push((None, file.start - position));
+            }
+            runs.push((Some(file.id), file.length));
+            position = file.start + file.length;
+        }
+        if self.size > position {
+            runs.push((None, self.size - position));
+        }
+        runs
+    }
+
+    // The notation of the puzzle text, one character per block, e.g.
+    // `00...111...2...333.44.5555.6666.777.888899`.
+    pub fn render(&self) -> String {
+        self.get_runs()
+            .into_iter()
+            .map(|(id, length)| match id {
+                Some(id) => id.to_string().repeat(length),
+                None => ".".repeat(length),
+            })
+            .collect()
+    }
+
+    // One token per run, `id` or `.` followed by `x` and the length when it
+    // is longer than one block, e.g. `0x2 .x3 10 .`. Unlike `render` it stays
+    // readable for ids with more than one digit.
+    pub fn render_compact(&self) -> String {
+        self.get_runs()
+            .into_iter()
+            .map(|(id, length)| {
+                let block = id.map_or(".".to_string(), |id| id.to_string());
+                if length == 1 {
+                    block
+                } else {
+                    format!("{}x{}", block, length)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    pub fn get_checksum(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| file.id * (2 * file.start + file.length - 1) * file.length / 2)
+            .sum()
+    }
+}
+
+// Moves `length` blocks of file `id` from `from..from + length` to
+// `to..to + length`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub id: usize,
+    pub from: usize,
+    pub to: usize,
+    pub length: usize,
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.files.iter().all(|file| file.id < 10) {
+            write!(f, "{}", self.render())
+        } else {
+            write!(f, "{}", self.render_compact())
+        }
+    }
+}
+
+// Yields the layout after each move, starting from the uncompacted disk.
+pub struct Replay {
+    blocks: Vec<Option<usize>>,
+    moves: std::vec::IntoIter<Move>,
+}
+
+impl Iterator for Replay {
+    type Item = Layout;
+
+    fn next(&mut self) -> Option<Layout> {
+        let Move {
+            id,
+            from,
+            to,
+            length,
+        } = self.moves.next()?;
+        self.blocks[from..from + length].fill(None);
+        self.blocks[to..to + length].fill(Some(id));
+        Some(Layout::from_blocks(&self.blocks))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DiskMap {
+    pub files: Vec<File>,
+    pub gaps: Vec<Gap>,
+    pub size: usize,
+}
+
+impl DiskMap {
+    pub fn new(disk_map_string: &str) -> Self {
+        let mut files = vec![];
+        let mut gaps: Vec<Gap> = vec![];
+        let mut position = 0;
+        for (i, digit) in disk_map_string.trim_end().chars().enumerate() {
+            let length = digit
+                .to_digit(10)
+                .expect("The disk map should only contain digits")
+                as usize;
+            if i % 2 == 0 {
+                files.push(File {
+                    id: i / 2,
+                    start: position,
+                    length,
+                });
+            } else if length > 0 {
+                // An empty file leaves two gaps next to each other.
+                match gaps.last_mut() {
+                    Some(gap) if gap.start + gap.length == position => gap.length += length,
+                    _ => gaps.push(Gap {
+                        start: position,
+                        length,
+                    }),
+                }
+            }
+            position += length;
+        }
+        DiskMap {
+            files,
+            gaps,
+            size: position,
+        }
+    }
+
+    pub fn get_layout(&self) -> Layout {
+        Layout::new(self.files.clone(), self.size)
+    }
+
+    pub fn compact_blocks(&self) -> Layout {
+        self.get_block_moves().0
+    }
+
+    pub fn compact_files(&self) -> Layout {
+        self.get_file_moves().0
+    }
+
+    // One frame per block, as in the puzzle text.
+    pub fn replay_blocks(&self) -> Replay {
+        let moves = self
+            .get_block_moves()
+            .1
+            .into_iter()
+            .flat_map(|span| {
+                (0..span.length).map(move |offset| Move {
+                    id: span.id,
+                    from: span.from + span.length - 1 - offset,
+                    to: span.to + offset,
+                    length: 1,
+                })
+            })
+            .collect::<Vec<Move>>();
+        Replay {
+            blocks: self.get_layout().get_blocks(),
+            moves: moves.into_iter(),
+        }
+    }
+
+    // One frame per moved file.
+    pub fn replay_files(&self) -> Replay {
+        Replay {
+            blocks: self.get_layout().get_blocks(),
+            moves: self.get_file_moves().1.into_iter(),
+        }
+    }
+
+    // Moves blocks one at a time from the end of the disk to the leftmost
+    // free block, working on whole spans instead of single blocks.
+    fn get_block_moves(&self) -> (Layout, Vec<Move>) {
+        let mut pending = self.files.clone();
+        let mut moved = vec![];
+        let mut moves = vec![];
+        'gaps: for &gap in self.gaps.iter() {
+            let mut gap = gap;
+            while gap.length > 0 {
+                while pending.last().is_some_and(|file| file.length == 0) {
+                    pending.pop();
+                }
+                let Some(file) = pending.last_mut() else {
+                    break 'gaps;
+                };
+                if file.start < gap.start {
+                    break 'gaps;
+                }
+                let length = gap.length.min(file.length);
+                moved.push(File {
+                    id: file.id,
+                    start: gap.start,
+                    length,
+                });
+                file.length -= length;
+                moves.push(Move {
+                    id: file.id,
+                    from: file.start + file.length,
+                    to: gap.start,
+                    length,
+                });
+                gap.start += length;
+                gap.length -= length;
+            }
+        }
+        pending.extend(moved);
+        (Layout::new(pending, self.size), moves)
+    }
+
+    // Moves every file once, in decreasing id order, to the leftmost gap
+    // that can hold it. Gaps are kept in one min-heap per length so that
+    // each lookup only has to check MAX_SPAN_LENGTH heap tops. Files are at
+    // most MAX_SPAN_LENGTH long, so longer gaps share the last heap.
+    fn get_file_moves(&self) -> (Layout, Vec<Move>) {
+        let mut moves = vec![];
+        let mut gaps: Vec<BinaryHeap<Reverse<(usize, usize)>>> =
+            vec![BinaryHeap::new(); MAX_SPAN_LENGTH + 1];
+        let push_gap = |gaps: &mut Vec<BinaryHeap<_>>, start: usize, length: usize| {
+            if length > 0 {
+                gaps[length.min(MAX_SPAN_LENGTH)].push(Reverse((start, length)));
+            }
+        };
+        for gap in self.gaps.iter() {
+            push_gap(&mut gaps, gap.start, gap.length);
+        }
+        let mut files = self.files.clone();
+        for file in files.iter_mut().rev().filter(|file| file.length > 0) {
+            let best_heap = (file.length..=MAX_SPAN_LENGTH)
+                .filter_map(|heap| gaps[heap].peek().map(|&Reverse(gap)| (gap, heap)))
+                .filter(|&((start, _), _)| start < file.start)
+                .min();
+            if let Some(((start, length), heap)) = best_heap {
+                gaps[heap].pop();
+                push_gap(&mut gaps, start + file.length, length - file.length);
+                moves.push(Move {
+                    id: file.id,
+                    from: file.start,
+                    to: start,
+                    length: file.length,
+                });
+                file.start = start;
+            }
+        }
+        (Layout::new(files, self.size), moves)
+    }
+}
@@ -1,158 +1,287 @@
+mod disk_map;
 use std::fs;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-struct File {
-    id: usize,
-    repetitions: usize,
-    spaces: usize,
-}
+use disk_map::DiskMap;
 
-#[derive(PartialEq, Eq)]
-struct DiskMap {
-    files: Vec<File>,
+#[derive(PartialEq, Eq, Debug)]
+enum Strategy {
+    Blocks,
+    Files,
 }
 
-impl DiskMap {
-    pub fn new(disk_map_string: &str) -> Self {
-        let fixed_input = disk_map_string.to_owned() + "0";
-        let enumerated_chars = fixed_input.as_str().chars().enumerate();
-
-        let spaces: Vec<usize> = enumerated_chars
-            .clone()
-            .filter(|(i, _)| i % 2 == 1)
-            .map(|(_, x)| x.to_digit(10).unwrap() as usize)
-            .collect();
-
-        let mut aux_files: Vec<File> = enumerated_chars
-            .filter(|(i, _)| i % 2 == 0)
-            .enumerate()
-            .map(|(id, (_, x))| File {
-                id,
-                repetitions: x.to_digit(10).unwrap() as usize,
-                spaces: spaces[id],
-            })
-            .collect();
-
-        let mut files: Vec<File> = vec![];
-        let mut i = 0;
-        while i < aux_files.len() - 1 {
-            let mut flag = false;
-            if aux_files[i + 1].repetitions == 0 {
-                aux_files[i].spaces += aux_files[i + 1].spaces;
-                flag = true;
-            }
-            files.push(aux_files[i]);
-            i += if flag { 2 } else { 1 };
-        }
-        files.push(*aux_files.last().unwrap());
-
-        DiskMap { files }
-    }
+struct Options {
+    input_filename: String,
+    replay: Option<Strategy>,
+}
 
-    pub fn get_fragmented_disk_checksum(&self) -> usize {
-        let mut result: Vec<usize> = Vec::new();
-        let repeated_files = self
-            .files
-            .iter()
-            .flat_map(|file| vec![file.id; file.repetitions].into_iter())
-            .collect::<Vec<usize>>();
-
-        let size = repeated_files.len();
-        let mut aux_files = repeated_files.clone();
-        let mut rev_free_spaces: Vec<usize> = self.files.iter().map(|x| x.spaces).rev().collect();
-
-        while !aux_files.is_empty() && result.len() < size {
-            repeated_files.windows(2).for_each(|file_id_window| {
-                let (a, b) = (file_id_window[0], file_id_window[1]);
-                result.push(a);
-                if a != b && !aux_files.is_empty() {
-                    let space_repetition = rev_free_spaces.pop().unwrap();
-                    for _ in 0..space_repetition {
-                        result.push(aux_files.pop().unwrap());
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut replay = None;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => {
+                    replay = match args.next().as_deref() {
+                        Some("blocks") => Some(Strategy::Blocks),
+                        Some("files") => Some(Strategy::Files),
+                        _ => return Err("Option --replay expects blocks or files".to_string()),
                     }
                 }
-            });
-        }
-        result[..size]
-            .into_iter()
-            .enumerate()
-            .map(|(i, file_id)| file_id * i as usize)
-            .sum()
-    }
-
-    pub fn get_fragmented_disk_checksum_with_whole_file(&self) -> usize {
-        let mut result: Vec<File> = self.files.clone();
-
-        let mut j = result.len() - 1;
-        loop {
-            let candidate = result[j];
-            for i in 0..j {
-                let start_file = result[i];
-                if start_file.spaces >= candidate.repetitions {
-                    result[i].spaces = 0;
-                    result.remove(j);
-                    result.insert(i + 1, candidate);
-                    result[i + 1].spaces = start_file
-                        .spaces
-                        .checked_sub(candidate.repetitions)
-                        .unwrap();
-                    result[j].spaces += candidate.repetitions + candidate.spaces;
-                    j += 1;
-                    break;
-                }
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
             }
-            if j == 0 {
-                break;
-            }
-            j -= 1;
         }
-        result
-            .into_iter()
-            .flat_map(|file| [&vec![file.id; file.repetitions][..], &vec![0; file.spaces]].concat())
-            .enumerate()
-            .map(|(i, file_id)| i * file_id as usize)
-            .sum()
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            replay,
+        })
     }
 }
 
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: day_09 <input> [--replay blocks|files]");
         std::process::exit(1)
     });
 
-    let disk_map_string_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let disk_map_string_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
 
     let disk_map = DiskMap::new(&disk_map_string_string);
-    let result_part1 = disk_map.get_fragmented_disk_checksum();
+    if let Some(strategy) = options.replay {
+        println!("{}", disk_map.get_layout());
+        let replay = match strategy {
+            Strategy::Blocks => disk_map.replay_blocks(),
+            Strategy::Files => disk_map.replay_files(),
+        };
+        for layout in replay {
+            println!("{}", layout);
+        }
+    }
+    let result_part1 = disk_map.compact_blocks().get_checksum();
     println!("Result part 1: {}", result_part1);
-    let result_part2 = disk_map.get_fragmented_disk_checksum_with_whole_file();
+    let result_part2 = disk_map.compact_files().get_checksum();
     println!("Result part 2: {}", result_part2);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use disk_map::{File, Layout};
 
     #[test]
     fn easy_example() {
         let disk_map = DiskMap::new("12345");
-        assert_eq!(disk_map.get_fragmented_disk_checksum(), 60);
+        assert_eq!(disk_map.compact_blocks().get_checksum(), 60);
     }
 
     #[test]
     fn complex_example() {
         let disk_map = DiskMap::new("2333133121414131402");
-        assert_eq!(disk_map.get_fragmented_disk_checksum(), 1928);
+        assert_eq!(disk_map.compact_blocks().get_checksum(), 1928);
     }
 
     #[test]
     fn example_with_whole_file() {
         let disk_map = DiskMap::new("2333133121414131402");
+        assert_eq!(disk_map.compact_files().get_checksum(), 2858);
+    }
+
+    #[test]
+    fn render() {
+        let disk_map = DiskMap::new("2333133121414131402");
+        assert_eq!(
+            disk_map.get_layout().to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+        assert_eq!(
+            disk_map.compact_blocks().to_string(),
+            "0099811188827773336446555566.............."
+        );
+        assert_eq!(
+            disk_map.compact_files().to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(
+            DiskMap::new("12345").get_layout().render_compact(),
+            "0 .x2 1x3 .x4 2x5"
+        );
+        let layout = DiskMap::new("1010101010101010101011").get_layout();
+        assert_eq!(layout.to_string(), "0 1 2 3 4 5 6 7 8 9 10 .");
+        assert_eq!(layout.render(), "012345678910.");
+    }
+
+    #[test]
+    fn replay_blocks() {
+        let frames: Vec<String> = DiskMap::new("12345")
+            .replay_blocks()
+            .map(|layout| layout.to_string())
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+        let disk_map = DiskMap::new("2333133121414131402");
+        assert_eq!(
+            disk_map.replay_blocks().last(),
+            Some(disk_map.compact_blocks())
+        );
+    }
+
+    #[test]
+    fn replay_files() {
+        let frames: Vec<String> = DiskMap::new("2333133121414131402")
+            .replay_files()
+            .map(|layout| layout.to_string())
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+    }
+
+    #[test]
+    fn options() {
+        let args = ["input.txt", "--replay", "files"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "input.txt");
+        assert_eq!(options.replay, Some(Strategy::Files));
+        assert!(Options::from_args(["--replay", "all"].map(String::from).into_iter()).is_err());
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let disk_map = DiskMap::new("2333133121414131402\n");
+        assert_eq!(disk_map, DiskMap::new("2333133121414131402"));
+        assert_eq!(disk_map.size, 42);
+    }
+
+    #[test]
+    fn compacted_layouts() {
+        let disk_map = DiskMap::new("12345");
+        let ids = |layout: &Layout| -> Vec<(usize, usize, usize)> {
+            layout
+                .files
+                .iter()
+                .map(|file| (file.id, file.start, file.length))
+                .collect()
+        };
+        assert_eq!(
+            ids(&disk_map.compact_blocks()),
+            vec![(0, 0, 1), (2, 1, 2), (1, 3, 3), (2, 6, 3)]
+        );
         assert_eq!(
-            disk_map.get_fragmented_disk_checksum_with_whole_file(),
-            2858
+            ids(&disk_map.compact_files()),
+            vec![(0, 0, 1), (1, 3, 3), (2, 10, 5)]
         );
+        let layout = DiskMap::new("2333133121414131402").compact_files();
+        assert_eq!(
+            layout.files[1],
+            File {
+                id: 9,
+                start: 2,
+                length: 2
+            }
+        );
+        assert_eq!(layout.size, 42);
+    }
+
+    #[test]
+    fn empty_files_and_gaps() {
+        let disk_map = DiskMap::new("10101");
+        assert_eq!(disk_map.compact_blocks().get_checksum(), 5);
+        let disk_map = DiskMap::new("1091");
+        assert_eq!(disk_map.compact_blocks().get_checksum(), 45);
+        assert_eq!(disk_map.compact_files().get_checksum(), 45);
+    }
+
+    fn naive_compaction(disk_map: &str, whole_files: bool) -> usize {
+        let mut blocks: Vec<Option<usize>> = vec![];
+        for (i, digit) in disk_map.chars().enumerate() {
+            let id = (i % 2 == 0).then_some(i / 2);
+            blocks.extend(vec![id; digit.to_digit(10).unwrap() as usize]);
+        }
+        if whole_files {
+            for id in (0..=disk_map.len() / 2).rev() {
+                let Some(start) = blocks.iter().position(|&block| block == Some(id)) else {
+                    continue;
+                };
+                let length = blocks[start..]
+                    .iter()
+                    .take_while(|&&block| block == Some(id))
+                    .count();
+                if let Some(gap) = (0..start).find(|&gap| {
+                    blocks[gap..gap + length]
+                        .iter()
+                        .all(|block| block.is_none())
+                }) {
+                    blocks[gap..gap + length].fill(Some(id));
+                    blocks[start..start + length].fill(None);
+                }
+            }
+        } else {
+            let (mut left, mut right) = (0, blocks.len());
+            while left < right {
+                if blocks[left].is_some() {
+                    left += 1;
+                } else if blocks[right - 1].is_none() {
+                    right -= 1;
+                } else {
+                    blocks.swap(left, right - 1);
+                }
+            }
+        }
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| i * block.unwrap_or(0))
+            .sum()
+    }
+
+    #[test]
+    fn matches_naive_compaction() {
+        for disk_map in [
+            "12345",
+            "2333133121414131402",
+            "9",
+            "10101",
+            // Empty files leave gaps next to each other.
+            "1909090901",
+            "0903",
+            "14113",
+            "9999999999",
+            "1313165",
+            // More than ten files, so ids have two digits.
+            "111111111111111111111111",
+            "2112112112112112112112119",
+        ] {
+            let map = DiskMap::new(disk_map);
+            assert_eq!(
+                map.compact_blocks().get_checksum(),
+                naive_compaction(disk_map, false),
+                "{}",
+                disk_map
+            );
+            assert_eq!(
+                map.compact_files().get_checksum(),
+                naive_compaction(disk_map, true),
+                "{}",
+                disk_map
+            );
+        }
     }
 }
@@ -1,94 +1,132 @@
-use std::collections::HashMap;
+mod parser;
+mod rules;
+mod stone_line;
 use std::fs;
 
+use num_bigint::BigUint;
+use parser::read_rules;
+use rules::{BlinkError, RuleSet};
+use stone_line::StoneLine;
+
+const USAGE: &str = "Usage: day_11 <input> [--blinks 75] [--rules <file>] [--help]";
+
 fn line_to_stones(line: String) -> Vec<u64> {
     line.split_whitespace()
         .map(|stone_string| stone_string.parse::<u64>().unwrap())
         .collect()
 }
 
-fn single_blink(stone: u64) -> Vec<u64> {
-    match stone {
-        0 => vec![1],
-        stone if stone.to_string().len() % 2 == 0 => {
-            let stone_str = stone.to_string();
-            let middle = stone_str.len() / 2;
-            vec![
-                stone_str[..middle].parse::<u64>().unwrap(),
-                stone_str[middle..].parse::<u64>().unwrap(),
-            ]
-        }
-        _ => vec![2024 * stone],
-    }
+fn count_stones(stones: &[u64], rules: &RuleSet, blinks: usize) -> Result<BigUint, BlinkError> {
+    Ok(StoneLine::new(stones).blink_times(rules, blinks)?.count())
 }
 
-fn blink(stones: &Vec<u64>) -> Vec<u64> {
-    stones
-        .into_iter()
-        .flat_map(|&stone| single_blink(stone))
-        .collect()
-}
-
-fn part_1(mut stones: Vec<u64>) -> usize {
-    for _ in 0..25 {
-        stones = blink(&stones);
+fn load_rules(filename: &str) -> Result<RuleSet, String> {
+    let rules_string =
+        fs::read_to_string(filename).map_err(|error| format!("{}: {}", filename, error))?;
+    match read_rules(&rules_string) {
+        Ok(("", rules)) => Ok(rules),
+        Ok((rest, _)) => Err(format!(
+            "{}: invalid rule: {}",
+            filename,
+            rest.lines().next().unwrap_or_default()
+        )),
+        Err(error) => Err(format!("{}: {}", filename, error)),
     }
-    stones.len()
 }
 
-fn part_2(mut stones: Vec<u64>) -> usize {
-    let mut result = 0;
-    let mut table = HashMap::new();
-
-    // 15 blinks
-    for _ in 0..15 {
-        stones = blink(&stones);
-    }
+struct Options {
+    input_filename: String,
+    blinks: Option<usize>,
+    rules_filename: Option<String>,
+    help: bool,
+}
 
-    for stone in stones {
-        let mut mid_frontier = vec![stone];
-        // 30 + 15 = 45 blinks
-        for _ in 0..30 {
-            mid_frontier = blink(&mid_frontier);
-            table.insert(stone, mid_frontier.len());
-        }
-        for frontier_stone in mid_frontier {
-            // 30 + 30 + 15 = 75 blinks
-            if table.contains_key(&frontier_stone) {
-                result += table[&frontier_stone];
-            } else {
-                let mut last_frontier = vec![frontier_stone];
-                for _ in 0..30 {
-                    last_frontier = blink(&last_frontier);
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut blinks = None;
+        let mut rules_filename = None;
+        let mut help = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--blinks" => {
+                    blinks = Some(
+                        args.next()
+                            .and_then(|blinks| blinks.parse().ok())
+                            .ok_or("Option --blinks expects a positive integer")?,
+                    )
+                }
+                "--help" => help = true,
+                "--rules" => {
+                    rules_filename = Some(args.next().ok_or("Option --rules expects a file")?)
                 }
-                table.insert(frontier_stone, last_frontier.len());
-                result += last_frontier.len();
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
             }
         }
+        let input_filename = match input_filename {
+            Some(input_filename) => input_filename,
+            None if help => String::new(),
+            None => return Err("Please, provide the input file as argument".to_string()),
+        };
+        Ok(Options {
+            input_filename,
+            blinks,
+            rules_filename,
+            help,
+        })
+    }
+}
+
+fn print_result(label: &str, stones: &[u64], rules: &RuleSet, blinks: usize) {
+    match count_stones(stones, rules, blinks) {
+        Ok(result) => println!("{}: {}", label, result),
+        Err(error) => println!("{}: {}", label, error),
     }
-    result
 }
 
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("{}", USAGE);
         std::process::exit(1)
     });
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
 
-    let stones_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let stones_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
     let stones: Vec<u64> = line_to_stones(stones_string);
+    let rules = match &options.rules_filename {
+        Some(filename) => load_rules(filename).unwrap_or_else(|message| {
+            eprintln!("{}", message);
+            std::process::exit(1)
+        }),
+        None => RuleSet::aoc(),
+    };
 
-    let result_part1 = part_1(stones.clone());
-    println!("Result part 1: {}", result_part1);
+    if let Some(blinks) = options.blinks {
+        print_result("Result", &stones, &rules, blinks);
+        return;
+    }
 
-    let result_part2 = part_2(stones);
-    println!("Result part 2: {}", result_part2);
+    print_result("Result part 1", &stones, &rules, 25);
+    print_result("Result part 2", &stones, &rules, 75);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rules::{split_digits, Predicate, Rule, Transform};
+
+    fn blink(stones: &[u64]) -> Vec<u64> {
+        stones
+            .iter()
+            .flat_map(|&stone| RuleSet::aoc().apply(stone).unwrap())
+            .collect()
+    }
 
     #[test]
     fn one_blink() {
@@ -118,4 +156,119 @@ mod tests {
         let output = "2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2";
         assert_eq!(stones, line_to_stones(output.to_string()));
     }
+
+    #[test]
+    fn digit_splitting() {
+        assert_eq!(split_digits(0), None);
+        assert_eq!(split_digits(7), None);
+        assert_eq!(split_digits(10), Some((1, 0)));
+        assert_eq!(split_digits(253000), Some((253, 0)));
+        assert_eq!(split_digits(1000), Some((10, 0)));
+        assert_eq!(split_digits(99999), None);
+        assert_eq!(split_digits(u64::MAX), Some((1844674407, 3709551615)));
+        assert_eq!(
+            split_digits(10_000_000_000_000_000_000),
+            Some((1_000_000_000, 0))
+        );
+    }
+
+    #[test]
+    fn stone_line() {
+        let stones = line_to_stones("125 17".to_string());
+        let line = StoneLine::new(&stones)
+            .blink_times(&RuleSet::aoc(), 6)
+            .unwrap();
+        assert_eq!(line.count(), BigUint::from(22_u64));
+        assert_eq!(
+            count_stones(&stones, &RuleSet::aoc(), 25),
+            Ok(BigUint::from(55312_u64))
+        );
+        assert_eq!(
+            count_stones(&stones, &RuleSet::aoc(), 75),
+            Ok(BigUint::from(65601038650482_u64))
+        );
+    }
+
+    #[test]
+    fn huge_blink_counts() {
+        let stones = line_to_stones("125 17".to_string());
+        let count = count_stones(&stones, &RuleSet::aoc(), 500).unwrap();
+        assert!(count > BigUint::from(u128::MAX));
+        assert!(count > count_stones(&stones, &RuleSet::aoc(), 499).unwrap());
+    }
+
+    #[test]
+    fn options() {
+        let args = ["input.txt", "--blinks", "500", "--rules", "rules.txt"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "input.txt");
+        assert_eq!(options.blinks, Some(500));
+        assert_eq!(options.rules_filename, Some("rules.txt".to_string()));
+        assert!(Options::from_args(["--blinks"].map(String::from).into_iter()).is_err());
+        let options = Options::from_args(["--help"].map(String::from).into_iter()).unwrap();
+        assert!(options.help);
+        assert!(
+            !Options::from_args(["input.txt"].map(String::from).into_iter())
+                .unwrap()
+                .help
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        // Ones vanish, even stones turn into two ones, threes are incremented
+        // and stones that no rule matches stay put.
+        let rules = RuleSet::new(vec![
+            Rule {
+                predicate: Predicate::Equals(1),
+                transform: Transform::Replace(vec![]),
+            },
+            Rule {
+                predicate: Predicate::DivisibleBy(2),
+                transform: Transform::Replace(vec![1, 1]),
+            },
+            Rule {
+                predicate: Predicate::Equals(3),
+                transform: Transform::Add(1),
+            },
+        ]);
+        assert_eq!(rules.apply(3), Ok(vec![4]));
+        assert_eq!(rules.apply(5), Ok(vec![5]));
+        assert_eq!(count_stones(&[3, 5], &rules, 1), Ok(BigUint::from(2_u64)));
+        assert_eq!(count_stones(&[3, 5], &rules, 2), Ok(BigUint::from(3_u64)));
+        assert_eq!(count_stones(&[3, 5], &rules, 3), Ok(BigUint::from(1_u64)));
+
+        let overflowing = RuleSet::new(vec![Rule {
+            predicate: Predicate::Any,
+            transform: Transform::Multiply(u64::MAX),
+        }]);
+        assert_eq!(
+            count_stones(&[2], &overflowing, 1),
+            Err(BlinkError::StoneOverflow(2))
+        );
+        assert_eq!(
+            count_stones(&[2], &overflowing, 1).unwrap_err().to_string(),
+            "stone 2 grows past the 64-bit stone values"
+        );
+
+        let splitting = RuleSet::new(vec![Rule {
+            predicate: Predicate::Any,
+            transform: Transform::SplitDigits,
+        }]);
+        assert_eq!(
+            count_stones(&[1234, 56], &splitting, 1),
+            Ok(BigUint::from(4_u64))
+        );
+        assert_eq!(
+            count_stones(&[123], &splitting, 1),
+            Err(BlinkError::OddDigits(123))
+        );
+    }
+
+    #[test]
+    fn rules_file() {
+        let rules = load_rules("rules.txt").unwrap();
+        assert_eq!(rules, RuleSet::aoc());
+        assert!(load_rules("missing.txt").is_err());
+    }
 }
@@ -0,0 +1,108 @@
+use super::rules::{Predicate, Rule, RuleSet, Transform};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{multispace0, not_line_ending, space0, space1, u64},
+    combinator::{map, value},
+    multi::{many0, separated_list1},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+// One rule per line, e.g. `0 -> 1`, `even-digits -> split` or
+// `* -> mul 2024`. Lines starting with `#` are comments.
+pub fn read_rules(input: &str) -> IResult<&str, RuleSet> {
+    let (input, rules) = delimited(
+        skip_comments,
+        many0(terminated(read_rule, skip_comments)),
+        multispace0,
+    )(input)?;
+    Ok((input, RuleSet::new(rules)))
+}
+
+fn skip_comments(input: &str) -> IResult<&str, ()> {
+    value(
+        (),
+        tuple((
+            multispace0,
+            many0(terminated(preceded(tag("#"), not_line_ending), multispace0)),
+        )),
+    )(input)
+}
+
+fn read_rule(input: &str) -> IResult<&str, Rule> {
+    let (input, (predicate, transform)) = separated_pair(
+        read_predicate,
+        tuple((space0, tag("->"), space0)),
+        read_transform,
+    )(input)?;
+    Ok((
+        input,
+        Rule {
+            predicate,
+            transform,
+        },
+    ))
+}
+
+fn read_predicate(input: &str) -> IResult<&str, Predicate> {
+    alt((
+        value(Predicate::Any, tag("*")),
+        value(Predicate::EvenDigits, tag("even-digits")),
+        preceded(
+            tuple((tag("divisible"), space1)),
+            map(u64, Predicate::DivisibleBy),
+        ),
+        map(u64, Predicate::Equals),
+    ))(input)
+}
+
+fn read_transform(input: &str) -> IResult<&str, Transform> {
+    alt((
+        value(Transform::SplitDigits, tag("split")),
+        preceded(tuple((tag("mul"), space1)), map(u64, Transform::Multiply)),
+        preceded(tuple((tag("add"), space1)), map(u64, Transform::Add)),
+        map(separated_list1(space1, u64), Transform::Replace),
+    ))(input)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_rule() {
+        assert_eq!(
+            read_rule("divisible 3 -> 1 2 3"),
+            Ok((
+                "",
+                Rule {
+                    predicate: Predicate::DivisibleBy(3),
+                    transform: Transform::Replace(vec![1, 2, 3]),
+                }
+            ))
+        );
+        assert_eq!(
+            read_rule("*->add 7"),
+            Ok((
+                "",
+                Rule {
+                    predicate: Predicate::Any,
+                    transform: Transform::Add(7),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_read_rules() {
+        let input =
+            "# Advent of Code rules\n0 -> 1\neven-digits -> split\n\n# fallback\n* -> mul 2024\n";
+        assert_eq!(read_rules(input), Ok(("", RuleSet::aoc())));
+        assert_eq!(
+            read_rules("0 -> 1\nbogus -> 2\n").unwrap().0,
+            "bogus -> 2\n"
+        );
+    }
+}
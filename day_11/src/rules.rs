@@ -0,0 +1,120 @@
+use nom::lib::std::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BlinkError {
+    StoneOverflow(u64),
+    OddDigits(u64),
+}
+
+impl fmt::Display for BlinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlinkError::StoneOverflow(stone) => {
+                write!(f, "stone {} grows past the 64-bit stone values", stone)
+            }
+            BlinkError::OddDigits(stone) => write!(
+                f,
+                "stone {} has an odd number of digits and cannot be split",
+                stone
+            ),
+        }
+    }
+}
+
+pub fn split_digits(stone: u64) -> Option<(u64, u64)> {
+    let digits = stone.checked_ilog10()? + 1;
+    if digits % 2 != 0 {
+        return None;
+    }
+    let divisor = 10_u64.pow(digits / 2);
+    Some((stone / divisor, stone % divisor))
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Predicate {
+    Any,
+    Equals(u64),
+    EvenDigits,
+    DivisibleBy(u64),
+}
+
+impl Predicate {
+    pub fn matches(&self, stone: u64) -> bool {
+        match *self {
+            Predicate::Any => true,
+            Predicate::Equals(value) => stone == value,
+            Predicate::EvenDigits => split_digits(stone).is_some(),
+            Predicate::DivisibleBy(divisor) => divisor != 0 && stone.is_multiple_of(divisor),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Transform {
+    Replace(Vec<u64>),
+    SplitDigits,
+    Multiply(u64),
+    Add(u64),
+}
+
+impl Transform {
+    pub fn apply(&self, stone: u64) -> Result<Vec<u64>, BlinkError> {
+        match self {
+            Transform::Replace(stones) => Ok(stones.clone()),
+            Transform::SplitDigits => split_digits(stone)
+                .map(|(left, right)| vec![left, right])
+                .ok_or(BlinkError::OddDigits(stone)),
+            Transform::Multiply(factor) => stone
+                .checked_mul(*factor)
+                .map(|stone| vec![stone])
+                .ok_or(BlinkError::StoneOverflow(stone)),
+            Transform::Add(term) => stone
+                .checked_add(*term)
+                .map(|stone| vec![stone])
+                .ok_or(BlinkError::StoneOverflow(stone)),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Rule {
+    pub predicate: Predicate,
+    pub transform: Transform,
+}
+
+// Rules are tried in order and the first matching one is applied. Stones
+// that no rule matches are left as they are.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        RuleSet { rules }
+    }
+
+    pub fn aoc() -> Self {
+        RuleSet::new(vec![
+            Rule {
+                predicate: Predicate::Equals(0),
+                transform: Transform::Replace(vec![1]),
+            },
+            Rule {
+                predicate: Predicate::EvenDigits,
+                transform: Transform::SplitDigits,
+            },
+            Rule {
+                predicate: Predicate::Any,
+                transform: Transform::Multiply(2024),
+            },
+        ])
+    }
+
+    pub fn apply(&self, stone: u64) -> Result<Vec<u64>, BlinkError> {
+        match self.rules.iter().find(|rule| rule.predicate.matches(stone)) {
+            Some(rule) => rule.transform.apply(stone),
+            None => Ok(vec![stone]),
+        }
+    }
+}
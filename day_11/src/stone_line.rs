@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use super::rules::{BlinkError, RuleSet};
+use num_bigint::BigUint;
+
+// Stones never interact, so the line is kept as a multiset of values: the
+// order is lost but the count is all that the puzzle asks for. Counts grow
+// about 1.5 times per blink, past u128 after some 200 blinks, so they are
+// kept as big integers.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct StoneLine {
+    counts: HashMap<u64, BigUint>,
+}
+
+impl StoneLine {
+    pub fn new(stones: &[u64]) -> Self {
+        let mut counts = HashMap::new();
+        for &stone in stones {
+            *counts.entry(stone).or_default() += 1_u32;
+        }
+        StoneLine { counts }
+    }
+
+    pub fn blink(&self, rules: &RuleSet) -> Result<Self, BlinkError> {
+        let mut counts: HashMap<u64, BigUint> = HashMap::new();
+        for (&stone, count) in self.counts.iter() {
+            for new_stone in rules.apply(stone)? {
+                *counts.entry(new_stone).or_default() += count;
+            }
+        }
+        Ok(StoneLine { counts })
+    }
+
+    pub fn blink_times(&self, rules: &RuleSet, blinks: usize) -> Result<Self, BlinkError> {
+        (0..blinks).try_fold(self.clone(), |line, _| line.blink(rules))
+    }
+
+    pub fn count(&self) -> BigUint {
+        self.counts.values().sum()
+    }
+}
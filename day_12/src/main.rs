@@ -1,5 +1,4 @@
-use itertools::Itertools;
-use std::{collections::HashSet, fs};
+use std::fs;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 struct Position {
@@ -7,212 +6,353 @@ struct Position {
     y: usize,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-struct Cell {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct BoundingBox {
+    min: Position,
+    max: Position,
+}
+
+impl BoundingBox {
+    fn new(position: Position) -> Self {
+        BoundingBox {
+            min: position,
+            max: position,
+        }
+    }
+
+    fn extend(&mut self, position: Position) {
+        self.min.x = self.min.x.min(position.x);
+        self.min.y = self.min.y.min(position.y);
+        self.max.x = self.max.x.max(position.x);
+        self.max.y = self.max.y.max(position.y);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Region {
     id: char,
-    position: Position,
+    area: usize,
+    perimeter: usize,
+    sides: usize,
+    bounding_box: BoundingBox,
 }
 
+impl Region {
+    fn get_price(&self) -> usize {
+        self.area * self.perimeter
+    }
+
+    fn get_discounted_price(&self) -> usize {
+        self.area * self.sides
+    }
+}
+
+const DIRECTIONS: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
 struct Map {
-    cells: Vec<Cell>,
+    plants: Vec<char>,
     n_rows: usize,
     n_cols: usize,
-    visited: HashSet<Cell>,
+}
+
+struct RegionLabels {
+    labels: Vec<usize>,
+    n_regions: usize,
 }
 
 impl Map {
     pub fn new(map_string: String) -> Self {
         let rows = map_string.lines().collect::<Vec<&str>>();
         let n_rows = rows.len();
-
-        let cells = rows
+        let plants = rows
             .into_iter()
-            .enumerate()
-            .flat_map(|(y, row)| {
-                row.chars().enumerate().map(move |(x, id)| Cell {
-                    id,
-                    position: Position { x: x + 1, y: y + 1 },
-                })
-            })
-            .collect::<Vec<Cell>>();
-
-        let n_cols = cells.len() / n_rows;
-        let visited = HashSet::new();
+            .flat_map(|row| row.chars())
+            .collect::<Vec<char>>();
+        let n_cols = plants.len() / n_rows;
         Map {
-            cells,
+            plants,
             n_rows,
             n_cols,
-            visited,
         }
     }
 
-    fn is_valid_position(&self, position: &Position) -> bool {
-        position.x <= self.n_cols && position.y <= self.n_rows
+    fn get_index(&self, x: i64, y: i64) -> Option<usize> {
+        if 0 <= x && (x as usize) < self.n_cols && 0 <= y && (y as usize) < self.n_rows {
+            Some(y as usize * self.n_cols + x as usize)
+        } else {
+            None
+        }
     }
 
-    fn get_cell(&self, position: &Position) -> Cell {
-        if position.x == 0 || position.y == 0 || !self.is_valid_position(position) {
-            Cell {
-                id: '.',
-                position: *position,
-            }
-        } else {
-            self.cells[(position.y - 1) * self.n_rows + (position.x - 1)]
+    fn get_position(&self, index: usize) -> Position {
+        Position {
+            x: index % self.n_cols,
+            y: index / self.n_cols,
         }
     }
 
-    fn get_neighbours(&self, cell: Cell) -> Vec<Cell> {
-        let position = cell.position;
-        vec![
-            Position {
-                x: position.x,
-                y: position.y + 1,
-            },
-            Position {
-                x: position.x + 1,
-                y: position.y,
-            },
-            Position {
-                x: position.x,
-                y: position.y - 1,
-            },
-            Position {
-                x: position.x - 1,
-                y: position.y,
-            },
-        ]
-        .into_iter()
-        .map(|position| self.get_cell(&position))
-        .collect()
-    }
-
-    fn get_diagonal(&self, first_neighbour: Cell, second_neighbour: Cell, current: Cell) -> Cell {
-        let position = if current.position.x == first_neighbour.position.x {
-            Position {
-                x: second_neighbour.position.x,
-                y: first_neighbour.position.y,
+    fn get_neighbour_indices(&self, index: usize) -> impl Iterator<Item = Option<usize>> + '_ {
+        let position = self.get_position(index);
+        DIRECTIONS
+            .into_iter()
+            .map(move |(dx, dy)| self.get_index(position.x as i64 + dx, position.y as i64 + dy))
+    }
+
+    fn label_regions(&self) -> RegionLabels {
+        let mut labels = vec![usize::MAX; self.plants.len()];
+        let mut n_regions = 0;
+        let mut stack = vec![];
+        for start in 0..self.plants.len() {
+            if labels[start] != usize::MAX {
+                continue;
             }
-        } else {
-            Position {
-                x: first_neighbour.position.x,
-                y: second_neighbour.position.y,
+            labels[start] = n_regions;
+            stack.push(start);
+            while let Some(index) = stack.pop() {
+                for neighbour in self.get_neighbour_indices(index).flatten() {
+                    if labels[neighbour] == usize::MAX
+                        && self.plants[neighbour] == self.plants[index]
+                    {
+                        labels[neighbour] = n_regions;
+                        stack.push(neighbour);
+                    }
+                }
             }
-        };
-        self.get_cell(&position)
+            n_regions += 1;
+        }
+        RegionLabels { labels, n_regions }
     }
 
-    fn step(&mut self, cell: Cell) -> (Vec<Cell>, usize) {
-        self.visited.insert(cell);
-        let all_neighbours = self.get_neighbours(cell);
-        let (equal_neighbours, unequal_neighbours): (Vec<Cell>, Vec<Cell>) = all_neighbours
-            .clone()
-            .into_iter()
-            .partition(|neighbour| neighbour.id == cell.id);
-
-        let mut all_neighbours = self.get_neighbours(cell);
-        all_neighbours.push(all_neighbours[0]);
-        let direct_vertex = all_neighbours
-            .windows(2)
-            .filter(|cells| {
-                unequal_neighbours.contains(&cells[0]) && unequal_neighbours.contains(&cells[1])
-            })
-            .count();
-        let undirect_vertex = all_neighbours
-            .windows(2)
-            .filter(|cells| {
-                equal_neighbours.contains(&cells[0])
-                    && equal_neighbours.contains(&cells[1])
-                    && self.get_diagonal(cells[0], cells[1], cell).id != cell.id
-            })
-            .count();
-
-        let unvisited_neighbours = equal_neighbours
-            .into_iter()
-            .filter(|neighbour| !self.visited.contains(neighbour))
-            .collect();
+    fn get_regions(&self) -> Vec<Region> {
+        let RegionLabels { labels, n_regions } = self.label_regions();
+        let mut regions: Vec<Option<Region>> = vec![None; n_regions];
+        let same_region = |index: usize, x: i64, y: i64| {
+            self.get_index(x, y)
+                .is_some_and(|other| labels[other] == labels[index])
+        };
 
-        (unvisited_neighbours, direct_vertex + undirect_vertex)
-    }
+        for (index, &label) in labels.iter().enumerate() {
+            let position = self.get_position(index);
+            let (x, y) = (position.x as i64, position.y as i64);
+            let perimeter = DIRECTIONS
+                .iter()
+                .filter(|(dx, dy)| !same_region(index, x + dx, y + dy))
+                .count();
+            // Each corner of a region closes one side, so counting convex
+            // and concave corners around every cell counts the sides.
+            let corners = (0..DIRECTIONS.len())
+                .filter(|&direction| {
+                    let (first_dx, first_dy) = DIRECTIONS[direction];
+                    let (second_dx, second_dy) = DIRECTIONS[(direction + 1) % DIRECTIONS.len()];
+                    let first = same_region(index, x + first_dx, y + first_dy);
+                    let second = same_region(index, x + second_dx, y + second_dy);
+                    let diagonal =
+                        same_region(index, x + first_dx + second_dx, y + first_dy + second_dy);
+                    (!first && !second) || (first && second && !diagonal)
+                })
+                .count();
 
-    fn recursive_step(&mut self, cell: Cell) -> (Vec<Cell>, usize) {
-        if self.visited.contains(&cell) {
-            return (vec![], 0);
+            let region = regions[label].get_or_insert(Region {
+                id: self.plants[index],
+                area: 0,
+                perimeter: 0,
+                sides: 0,
+                bounding_box: BoundingBox::new(position),
+            });
+            region.area += 1;
+            region.perimeter += perimeter;
+            region.sides += corners;
+            region.bounding_box.extend(position);
         }
-        let (mut equal_neighbours, mut partial_perimeter) = self.step(cell);
-        let size = equal_neighbours.clone().into_iter().count();
-        let (mut next_equal_neighbours, next_partial_perimeter) = if size > 0 {
-            equal_neighbours
-                .iter()
-                .map(|&next_cell| self.recursive_step(next_cell))
-                .fold(
-                    (vec![], 0),
-                    |(mut acc_neighbours, acc_perimeter),
-                     (mut next_equal_neighbours, mut next_partial_perimeter)| {
-                        next_equal_neighbours.append(&mut acc_neighbours);
-                        next_partial_perimeter += acc_perimeter;
-                        (next_equal_neighbours, next_partial_perimeter)
-                    },
-                )
-        } else {
-            (vec![], 0)
-        };
+        regions.into_iter().flatten().collect()
+    }
 
-        equal_neighbours.push(cell);
-        equal_neighbours.append(&mut next_equal_neighbours);
-        partial_perimeter += next_partial_perimeter;
-        (equal_neighbours, partial_perimeter)
+    fn render_regions(&self) -> String {
+        let RegionLabels { labels, .. } = self.label_regions();
+        let mut rendered = String::new();
+        for (index, &label) in labels.iter().enumerate() {
+            rendered.push_str(&format!(
+                "\x1b[48;5;{}m{}\x1b[0m",
+                16 + (label * 37) % 216,
+                self.plants[index]
+            ));
+            if (index + 1) % self.n_cols == 0 {
+                rendered.push('\n');
+            }
+        }
+        rendered
     }
+}
+
+struct Options {
+    input_filename: String,
+    show_regions: bool,
+}
 
-    fn get_price(&mut self) -> usize {
-        let mut result = 0;
-        for &cell in self.cells.clone().iter() {
-            if !self.visited.contains(&cell) {
-                let (path, partial_perimeter) = self.recursive_step(cell);
-                result += path.iter().unique().count() * partial_perimeter;
+impl Options {
+    fn from_args(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut show_regions = false;
+        for arg in args {
+            match arg.as_str() {
+                "--show-regions" => show_regions = true,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
             }
         }
-        result
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            show_regions,
+        })
     }
 }
 
+fn get_result_part1(regions: &[Region]) -> usize {
+    regions.iter().map(|region| region.get_price()).sum()
+}
+
+fn get_result_part2(regions: &[Region]) -> usize {
+    regions
+        .iter()
+        .map(|region| region.get_discounted_price())
+        .sum()
+}
+
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: day_12 <input> [--show-regions]");
         std::process::exit(1)
     });
 
-    let map_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
-    let mut map = Map::new(map_string);
-    let result_part1 = map.get_price();
-    println!("Result part 2: {}", result_part1);
+    let map_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
+    let map = Map::new(map_string);
+    if options.show_regions {
+        print!("{}", map.render_regions());
+    }
+    let regions = map.get_regions();
+
+    let result_part1 = get_result_part1(&regions);
+    println!("Result part 1: {}", result_part1);
+
+    let result_part2 = get_result_part2(&regions);
+    println!("Result part 2: {}", result_part2);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn read_regions(filename: &str) -> Vec<Region> {
+        let map_string =
+            fs::read_to_string(filename).expect("Should have been able to read the file");
+        Map::new(map_string).get_regions()
+    }
+
+    #[test]
+    fn options() {
+        let args = ["--show-regions", "input.txt"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "input.txt");
+        assert!(options.show_regions);
+        let options = Options::from_args(["input.txt".to_string()].into_iter()).unwrap();
+        assert!(!options.show_regions);
+        assert!(Options::from_args(["--show-regions".to_string()].into_iter()).is_err());
+        assert!(Options::from_args(["a.txt", "b.txt"].map(String::from).into_iter()).is_err());
+    }
+
     #[test]
     fn xo_example() {
-        let map_string =
-            fs::read_to_string("test.txt").expect("Should have been able to read the file");
-        let mut map = Map::new(map_string);
-        assert_eq!(map.get_price(), 436);
+        let regions = read_regions("test.txt");
+        assert_eq!(get_result_part1(&regions), 772);
+        assert_eq!(get_result_part2(&regions), 436);
     }
 
     #[test]
     fn e_example() {
-        let map_string =
-            fs::read_to_string("test2.txt").expect("Should have been able to read the file");
-        let mut map = Map::new(map_string);
-        assert_eq!(map.get_price(), 236);
+        let regions = read_regions("test2.txt");
+        assert_eq!(get_result_part1(&regions), 692);
+        assert_eq!(get_result_part2(&regions), 236);
     }
 
     #[test]
     fn ab_example() {
+        let regions = read_regions("test3.txt");
+        assert_eq!(get_result_part1(&regions), 1184);
+        assert_eq!(get_result_part2(&regions), 368);
+    }
+
+    #[test]
+    fn ab_labels() {
         let map_string =
             fs::read_to_string("test3.txt").expect("Should have been able to read the file");
-        let mut map = Map::new(map_string);
-        assert_eq!(map.get_price(), 368);
+        let map = Map::new(map_string);
+        let RegionLabels { labels, n_regions } = map.label_regions();
+        assert_eq!(n_regions, 3);
+        assert_eq!(&labels[0..6], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(&labels[6..12], &[0, 0, 0, 1, 1, 0]);
+        assert_eq!(&labels[18..24], &[0, 2, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn large_single_region() {
+        let row = "A".repeat(500);
+        let map = Map::new(vec![row; 500].join("\n"));
+        let regions = map.get_regions();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].area, 250000);
+        assert_eq!(regions[0].perimeter, 2000);
+        assert_eq!(regions[0].sides, 4);
+    }
+
+    #[test]
+    fn render_regions() {
+        let map = Map::new("AB\nAA\n".to_string());
+        let rendered = map.render_regions();
+        assert_eq!(rendered.lines().count(), 2);
+        assert!(rendered.starts_with("\x1b[48;5;16mA\x1b[0m\x1b[48;5;53mB"));
+    }
+
+    #[test]
+    fn ab_regions() {
+        let regions = read_regions("test3.txt");
+        assert_eq!(
+            regions,
+            vec![
+                Region {
+                    id: 'A',
+                    area: 28,
+                    perimeter: 40,
+                    sides: 12,
+                    bounding_box: BoundingBox {
+                        min: Position { x: 0, y: 0 },
+                        max: Position { x: 5, y: 5 },
+                    },
+                },
+                Region {
+                    id: 'B',
+                    area: 4,
+                    perimeter: 8,
+                    sides: 4,
+                    bounding_box: BoundingBox {
+                        min: Position { x: 3, y: 1 },
+                        max: Position { x: 4, y: 2 },
+                    },
+                },
+                Region {
+                    id: 'B',
+                    area: 4,
+                    perimeter: 8,
+                    sides: 4,
+                    bounding_box: BoundingBox {
+                        min: Position { x: 1, y: 3 },
+                        max: Position { x: 2, y: 4 },
+                    },
+                },
+            ]
+        );
     }
 }
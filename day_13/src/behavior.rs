@@ -1,45 +1,474 @@
-pub struct Solution {
-    pub a: u64,
-    pub b: u64,
+use nom::lib::std::fmt;
+
+// Upper bound on the press counts tried for the extra buttons of a machine.
+const SEARCH_BUDGET: i128 = 1_000_000;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CostModel {
+    pub button_costs: Vec<u64>,
+    pub press_limit: Option<u64>,
+    pub prize_offset: i64,
 }
 
-impl Solution {
-    pub fn new(a: f64, b: f64) -> Option<Self> {
-        if a.fract() == 0.0 && b.fract() == 0.0 {
-            Some(Solution {
-                a: a as u64,
-                b: b as u64,
-            })
-        } else {
-            None
+impl CostModel {
+    pub fn part1() -> Self {
+        CostModel {
+            button_costs: vec![3, 1],
+            press_limit: Some(100),
+            prize_offset: 0,
         }
     }
+
+    pub fn part2() -> Self {
+        CostModel {
+            button_costs: vec![3, 1],
+            press_limit: None,
+            prize_offset: 10000000000000,
+        }
+    }
+
+    fn get_cost(&self, button: usize) -> i128 {
+        self.button_costs[button] as i128
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Solution {
+    pub presses: Vec<u64>,
+    pub tokens: u64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Unsolvable {
+    FractionalPresses,
+    NegativePresses,
+    PressLimitExceeded,
+    OffLine,
+    NoIntegerCombination,
+    NoValidCombination,
+    UnboundedSearch,
+    CostCount { buttons: usize, costs: usize },
+}
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Unsolvable::CostCount { buttons, costs } => {
+                return write!(
+                    f,
+                    "the machine has {} buttons but {} costs were given",
+                    buttons, costs
+                )
+            }
+            Unsolvable::FractionalPresses => {
+                "the only solution needs a fractional number of presses"
+            }
+            Unsolvable::NegativePresses => "the only solution needs a negative number of presses",
+            Unsolvable::PressLimitExceeded => "the only solution exceeds the press limit",
+            Unsolvable::OffLine => "the prize is not on the line of the collinear buttons",
+            Unsolvable::NoIntegerCombination => {
+                "no integer combination of the collinear buttons reaches the prize"
+            }
+            Unsolvable::NoValidCombination => {
+                "no combination of the buttons is within the press bounds"
+            }
+            Unsolvable::UnboundedSearch => {
+                "the extra buttons need a tighter press limit to bound the search"
+            }
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Button {
+    pub x: i64,
+    pub y: i64,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Behavior {
-    pub x_a: i64,
-    pub x_b: i64,
+    pub buttons: Vec<Button>,
     pub x_prize: i64,
-    pub y_a: i64,
-    pub y_b: i64,
     pub y_prize: i64,
 }
 
 impl Behavior {
-    pub fn get_min_solution(&self) -> Option<Solution> {
-        let denominator_determinant = (self.x_a * self.y_b - self.x_b * self.y_a) as f64;
-        let x_determinant = (self.x_prize * self.y_b - self.x_b * self.y_prize) as f64;
-        let y_determinant = (self.x_a * self.y_prize - self.x_prize * self.y_a) as f64;
-
-        Solution::new(
-            x_determinant / denominator_determinant,
-            y_determinant / denominator_determinant,
+    pub fn get_min_solution(&self, cost_model: &CostModel) -> Result<Solution, Unsolvable> {
+        if cost_model.button_costs.len() != self.buttons.len() {
+            return Err(Unsolvable::CostCount {
+                buttons: self.buttons.len(),
+                costs: cost_model.button_costs.len(),
+            });
+        }
+        let prize = (
+            self.x_prize as i128 + cost_model.prize_offset as i128,
+            self.y_prize as i128 + cost_model.prize_offset as i128,
+        );
+        let press_limit = cost_model.press_limit.map(|limit| limit as i128);
+        let mut budget = SEARCH_BUDGET;
+        let presses = self.solve(0, prize, press_limit, cost_model, &mut budget)?;
+        let tokens = presses
+            .iter()
+            .enumerate()
+            .map(|(button, &presses)| presses * cost_model.get_cost(button))
+            .sum::<i128>();
+        Ok(Solution {
+            presses: presses.into_iter().map(|presses| presses as u64).collect(),
+            tokens: tokens as u64,
+        })
+    }
+
+    fn solve(
+        &self,
+        first: usize,
+        prize: (i128, i128),
+        press_limit: Option<i128>,
+        cost_model: &CostModel,
+        budget: &mut i128,
+    ) -> Result<Vec<i128>, Unsolvable> {
+        let buttons = &self.buttons[first..];
+        match buttons {
+            [] if prize == (0, 0) => Ok(vec![]),
+            [] => Err(Unsolvable::OffLine),
+            [button] => {
+                let pair = [*button, Button { x: 0, y: 0 }];
+                let costs = (cost_model.get_cost(first), 0);
+                solve_pair(pair, costs, prize, press_limit).map(|(a, _)| vec![a])
+            }
+            [first_button, second_button] => {
+                let costs = (cost_model.get_cost(first), cost_model.get_cost(first + 1));
+                solve_pair([*first_button, *second_button], costs, prize, press_limit)
+                    .map(|(a, b)| vec![a, b])
+            }
+            [_, _, _] if get_independent_pair(buttons).is_some() => {
+                self.solve_triple(first, prize, press_limit, cost_model, budget)
+            }
+            // With more buttons than dimensions, enumerate the presses of the
+            // first button and solve the remaining ones exactly.
+            [button, ..] => {
+                let limit = get_press_bound(button, &buttons[1..], prize, press_limit)
+                    .ok_or(Unsolvable::UnboundedSearch)?;
+                spend(budget, limit + 1)?;
+                let mut best: Option<(i128, Vec<i128>)> = None;
+                for presses in 0..=limit {
+                    let remaining = (
+                        prize.0 - presses * button.x as i128,
+                        prize.1 - presses * button.y as i128,
+                    );
+                    let rest =
+                        match self.solve(first + 1, remaining, press_limit, cost_model, budget) {
+                            Ok(rest) => rest,
+                            Err(Unsolvable::UnboundedSearch) => {
+                                return Err(Unsolvable::UnboundedSearch)
+                            }
+                            Err(_) => continue,
+                        };
+                    let tokens = presses * cost_model.get_cost(first)
+                        + rest
+                            .iter()
+                            .enumerate()
+                            .map(|(offset, &presses)| {
+                                presses * cost_model.get_cost(first + 1 + offset)
+                            })
+                            .sum::<i128>();
+                    if best
+                        .as_ref()
+                        .is_none_or(|(best_tokens, _)| tokens < *best_tokens)
+                    {
+                        let mut solution = vec![presses];
+                        solution.extend(rest);
+                        best = Some((tokens, solution));
+                    }
+                }
+                best.map(|(_, solution)| solution)
+                    .ok_or(Unsolvable::NoValidCombination)
+            }
+        }
+    }
+
+    // One button is pressed on top of two independent ones. The pair only
+    // has an integer solution when the presses of the extra button fall in
+    // some residue classes modulo a period that divides the determinant of
+    // the pair. Within a class every press count, and so the cost, is linear
+    // in the number of periods, so only the cheapest end of each class has to
+    // be checked.
+    fn solve_triple(
+        &self,
+        first: usize,
+        prize: (i128, i128),
+        press_limit: Option<i128>,
+        cost_model: &CostModel,
+        budget: &mut i128,
+    ) -> Result<Vec<i128>, Unsolvable> {
+        let buttons = &self.buttons[first..first + 3];
+        let (extra, (a_index, b_index)) =
+            get_independent_pair(buttons).ok_or(Unsolvable::UnboundedSearch)?;
+        let [extra_button, a_button, b_button] = [extra, a_index, b_index].map(|index| {
+            let button = buttons[index];
+            (button.x as i128, button.y as i128)
+        });
+        let (x_prize, y_prize) = prize;
+
+        // Cramer's rule for the pair after `e` presses of the extra button:
+        // a = (a_base - e * a_step) / determinant, and likewise for b.
+        let determinant = a_button.0 * b_button.1 - b_button.0 * a_button.1;
+        let a_base = x_prize * b_button.1 - b_button.0 * y_prize;
+        let a_step = extra_button.0 * b_button.1 - b_button.0 * extra_button.1;
+        let b_base = a_button.0 * y_prize - x_prize * a_button.1;
+        let b_step = a_button.0 * extra_button.1 - extra_button.0 * a_button.1;
+        let period = determinant.abs() / gcd(gcd(determinant, a_step), b_step);
+        let (a_delta, b_delta) = (
+            -period * a_step / determinant,
+            -period * b_step / determinant,
+        );
+
+        let extra_bound = get_press_bound(
+            &buttons[extra],
+            &[buttons[a_index], buttons[b_index]],
+            prize,
+            press_limit,
         )
+        .unwrap_or(i128::MAX);
+        let residues = period.min(extra_bound.saturating_add(1));
+        spend(budget, residues)?;
+
+        let costs = [extra, a_index, b_index].map(|index| cost_model.get_cost(first + index));
+        let slope = costs[0] * period + costs[1] * a_delta + costs[2] * b_delta;
+        let upper = press_limit.unwrap_or(i128::MAX);
+        let mut best: Option<(i128, Vec<i128>)> = None;
+        for residue in 0..residues {
+            let a_numerator = a_base - residue * a_step;
+            let b_numerator = b_base - residue * b_step;
+            if a_numerator % determinant != 0 || b_numerator % determinant != 0 {
+                continue;
+            }
+            let (a, b) = (a_numerator / determinant, b_numerator / determinant);
+            let Some((k_min, k_max)) = [
+                get_k_range(residue, period, 0, upper),
+                get_k_range(a, a_delta, 0, upper),
+                get_k_range(b, b_delta, 0, upper),
+            ]
+            .into_iter()
+            .try_fold((i128::MIN, i128::MAX), |(low, high), range| {
+                range.map(|(range_low, range_high)| (low.max(range_low), high.min(range_high)))
+            }) else {
+                continue;
+            };
+            if k_min > k_max {
+                continue;
+            }
+            let k = match slope.signum() {
+                -1 => k_max,
+                _ => k_min,
+            };
+            if k == i128::MIN || k == i128::MAX {
+                continue;
+            }
+
+            let mut presses = vec![0; 3];
+            presses[extra] = residue + k * period;
+            presses[a_index] = a + k * a_delta;
+            presses[b_index] = b + k * b_delta;
+            let tokens = (0..3)
+                .map(|index| presses[index] * cost_model.get_cost(first + index))
+                .sum::<i128>();
+            if best
+                .as_ref()
+                .is_none_or(|best| (tokens, &presses) < (best.0, &best.1))
+            {
+                best = Some((tokens, presses));
+            }
+        }
+        best.map(|(_, presses)| presses)
+            .ok_or(Unsolvable::NoValidCombination)
     }
+}
+
+// Picks two buttons with independent moves, returning the index of the
+// remaining button and the indices of the pair.
+fn get_independent_pair(buttons: &[Button]) -> Option<(usize, (usize, usize))> {
+    [(0, (1, 2)), (1, (0, 2)), (2, (0, 1))]
+        .into_iter()
+        .find(|&(_, (a, b))| {
+            buttons[a].x as i128 * buttons[b].y as i128
+                != buttons[b].x as i128 * buttons[a].y as i128
+        })
+}
+
+fn spend(budget: &mut i128, steps: i128) -> Result<(), Unsolvable> {
+    if steps > *budget {
+        return Err(Unsolvable::UnboundedSearch);
+    }
+    *budget -= steps;
+    Ok(())
+}
+
+fn get_press_bound(
+    button: &Button,
+    others: &[Button],
+    prize: (i128, i128),
+    press_limit: Option<i128>,
+) -> Option<i128> {
+    // On an axis where no button moves backwards, a button can't be pressed
+    // more times than it takes to overshoot the prize on that axis.
+    let axis_bound = |component: i64, prize: i128, others_move_forward: bool| {
+        (component > 0 && others_move_forward).then(|| prize.max(0) / component as i128)
+    };
+    let x_bound = axis_bound(button.x, prize.0, others.iter().all(|other| other.x >= 0));
+    let y_bound = axis_bound(button.y, prize.1, others.iter().all(|other| other.y >= 0));
+    [x_bound, y_bound, press_limit].into_iter().flatten().min()
+}
+
+fn solve_pair(
+    [first, second]: [Button; 2],
+    (first_cost, second_cost): (i128, i128),
+    prize: (i128, i128),
+    press_limit: Option<i128>,
+) -> Result<(i128, i128), Unsolvable> {
+    let (x_a, y_a, x_b, y_b) = (
+        first.x as i128,
+        first.y as i128,
+        second.x as i128,
+        second.y as i128,
+    );
+    let (x_prize, y_prize) = prize;
+
+    let denominator_determinant = x_a * y_b - x_b * y_a;
+    if denominator_determinant == 0 {
+        return solve_collinear_pair(
+            [first, second],
+            (first_cost, second_cost),
+            prize,
+            press_limit,
+        );
+    }
+    let x_determinant = x_prize * y_b - x_b * y_prize;
+    let y_determinant = x_a * y_prize - x_prize * y_a;
+
+    if x_determinant % denominator_determinant != 0 || y_determinant % denominator_determinant != 0
+    {
+        return Err(Unsolvable::FractionalPresses);
+    }
+    let a = x_determinant / denominator_determinant;
+    let b = y_determinant / denominator_determinant;
+    if a < 0 || b < 0 {
+        return Err(Unsolvable::NegativePresses);
+    }
+    if press_limit.is_some_and(|limit| a > limit || b > limit) {
+        return Err(Unsolvable::PressLimitExceeded);
+    }
+    Ok((a, b))
+}
+
+fn solve_collinear_pair(
+    [first, second]: [Button; 2],
+    (first_cost, second_cost): (i128, i128),
+    prize: (i128, i128),
+    press_limit: Option<i128>,
+) -> Result<(i128, i128), Unsolvable> {
+    let (x_a, y_a, x_b, y_b) = (
+        first.x as i128,
+        first.y as i128,
+        second.x as i128,
+        second.y as i128,
+    );
+    let (x_prize, y_prize) = prize;
+    if x_a * y_prize != x_prize * y_a || x_b * y_prize != x_prize * y_b {
+        return Err(Unsolvable::OffLine);
+    }
+
+    // Both buttons move along the same line, so a single axis describes
+    // every combination: a * step_a + b * step_b = target.
+    let (step_a, step_b, target) = if x_a != 0 || x_b != 0 {
+        (x_a, x_b, x_prize)
+    } else {
+        (y_a, y_b, y_prize)
+    };
+    if step_a == 0 && step_b == 0 {
+        return if x_prize == 0 && y_prize == 0 {
+            Ok((0, 0))
+        } else {
+            Err(Unsolvable::OffLine)
+        };
+    }
+
+    let (gcd, coefficient_a, coefficient_b) = extended_gcd(step_a, step_b);
+    if target % gcd != 0 {
+        return Err(Unsolvable::NoIntegerCombination);
+    }
+    let scale = target / gcd;
+    let (base_a, base_b) = (coefficient_a * scale, coefficient_b * scale);
+    // The solutions are a = base_a + k * delta_a and b = base_b - k * delta_b.
+    let (delta_a, delta_b) = (step_b / gcd, step_a / gcd);
+
+    let upper = press_limit.unwrap_or(i128::MAX);
+    let (k_min, k_max) = [
+        get_k_range(base_a, delta_a, 0, upper),
+        get_k_range(base_b, -delta_b, 0, upper),
+    ]
+    .into_iter()
+    .try_fold((i128::MIN, i128::MAX), |(low, high), range| {
+        range.map(|(range_low, range_high)| (low.max(range_low), high.min(range_high)))
+    })
+    .ok_or(Unsolvable::NoValidCombination)?;
+    if k_min > k_max {
+        return Err(Unsolvable::NoValidCombination);
+    }
+
+    let slope = first_cost * delta_a - second_cost * delta_b;
+    let k = match slope.signum() {
+        1 => k_min,
+        -1 => k_max,
+        _ if k_min != i128::MIN => k_min,
+        _ => k_max,
+    };
+    if k == i128::MIN || k == i128::MAX {
+        return Err(Unsolvable::NoValidCombination);
+    }
+    Ok((base_a + k * delta_a, base_b - k * delta_b))
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    extended_gcd(a, b).0
+}
+
+fn get_k_range(base: i128, step: i128, low: i128, high: i128) -> Option<(i128, i128)> {
+    // Values of k such that low <= base + k * step <= high.
+    if step == 0 {
+        return (low <= base && base <= high).then_some((i128::MIN, i128::MAX));
+    }
+    let (from, to) = if step > 0 {
+        (low - base, high.saturating_sub(base))
+    } else {
+        (high.saturating_sub(base), low - base)
+    };
+    Some((div_ceil(from, step), div_floor(to, step)))
+}
+
+fn div_floor(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    if numerator % denominator != 0 && (numerator < 0) != (denominator < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
 
-    pub fn set_unit_conversion_correction(&mut self) {
-        self.x_prize += 10000000000000;
-        self.y_prize += 10000000000000;
+fn div_ceil(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    if numerator % denominator != 0 && (numerator < 0) == (denominator < 0) {
+        quotient + 1
+    } else {
+        quotient
     }
 }
@@ -2,33 +2,117 @@ mod behavior;
 mod parser;
 use std::fs;
 
-use behavior::Behavior;
+use behavior::{Behavior, CostModel, Unsolvable};
 use parser::read_input;
 
-fn get_result(behaviors: Vec<Behavior>) -> u64 {
-    behaviors
-        .iter()
-        .filter_map(|behavior| behavior.get_min_solution())
-        .map(|solution| solution.a * 3 + solution.b)
-        .sum()
+struct Options {
+    input_filename: String,
+    cost_model: Option<CostModel>,
+    verbose: bool,
+}
+
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut cost_model: Option<CostModel> = None;
+        let mut verbose = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--costs" => {
+                    cost_model.get_or_insert_with(CostModel::part1).button_costs = args
+                        .next()
+                        .and_then(|costs| {
+                            costs
+                                .split(',')
+                                .map(|cost| cost.parse().ok())
+                                .collect::<Option<Vec<u64>>>()
+                        })
+                        .ok_or("Option --costs expects a comma separated list of costs")?
+                }
+                "--limit" => {
+                    cost_model.get_or_insert_with(CostModel::part1).press_limit = Some(
+                        args.next()
+                            .and_then(|limit| limit.parse().ok())
+                            .ok_or("Option --limit expects a positive integer")?,
+                    )
+                }
+                "--no-limit" => cost_model.get_or_insert_with(CostModel::part1).press_limit = None,
+                "--offset" => {
+                    cost_model.get_or_insert_with(CostModel::part1).prize_offset = args
+                        .next()
+                        .and_then(|offset| offset.parse().ok())
+                        .ok_or("Option --offset expects an integer")?
+                }
+                "--verbose" => verbose = true,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            cost_model,
+            verbose,
+        })
+    }
+}
+
+// A cost list that does not match the buttons of a machine is an error
+// rather than a reason to skip the machine.
+fn get_result(
+    behaviors: &[Behavior],
+    cost_model: &CostModel,
+    verbose: bool,
+) -> Result<u64, String> {
+    let mut result = 0;
+    for (index, behavior) in behaviors.iter().enumerate() {
+        match behavior.get_min_solution(cost_model) {
+            Ok(solution) => result += solution.tokens,
+            Err(reason @ Unsolvable::CostCount { .. }) => {
+                return Err(format!("Machine {}: {}", index + 1, reason))
+            }
+            Err(reason) => {
+                if verbose {
+                    println!("Machine {} is unsolvable: {}", index + 1, reason);
+                }
+            }
+        }
+    }
+    Ok(result)
 }
 
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!(
+            "Usage: day_13 <input> [--costs 3,1] [--limit 100 | --no-limit] [--offset 0] [--verbose]"
+        );
         std::process::exit(1)
     });
-    let behaviors_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let behaviors_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
+    let (_, behaviors) = read_input(&behaviors_string).unwrap();
 
-    let (_, mut behaviors) = read_input(&behaviors_string).unwrap();
-    let result_part_1: u64 = get_result(behaviors.clone());
+    let exit_on_error = |message: String| {
+        eprintln!("{}", message);
+        eprintln!("Use --costs to give one cost per button");
+        std::process::exit(1)
+    };
+
+    if let Some(cost_model) = options.cost_model {
+        let result =
+            get_result(&behaviors, &cost_model, options.verbose).unwrap_or_else(exit_on_error);
+        println!("Result: {}", result);
+        return;
+    }
+
+    let result_part_1 =
+        get_result(&behaviors, &CostModel::part1(), options.verbose).unwrap_or_else(exit_on_error);
     println!("Result part 1: {}", result_part_1);
 
-    behaviors
-        .iter_mut()
-        .for_each(|behavior| behavior.set_unit_conversion_correction());
-    let result_part_2: u64 = get_result(behaviors);
+    let result_part_2 =
+        get_result(&behaviors, &CostModel::part2(), options.verbose).unwrap_or_else(exit_on_error);
     println!("Result part 2: {}", result_part_2);
 }
 
@@ -36,13 +120,309 @@ fn main() {
 pub mod tests {
 
     use super::*;
+    use behavior::{Button, Solution};
+
+    fn behavior(buttons: &[(i64, i64)], prize: (i64, i64)) -> Behavior {
+        Behavior {
+            buttons: buttons.iter().map(|&(x, y)| Button { x, y }).collect(),
+            x_prize: prize.0,
+            y_prize: prize.1,
+        }
+    }
+
+    fn unlimited(button_costs: Vec<u64>) -> CostModel {
+        CostModel {
+            button_costs,
+            press_limit: None,
+            prize_offset: 0,
+        }
+    }
+
+    fn get_presses(behavior: &Behavior, cost_model: &CostModel) -> Result<Vec<u64>, Unsolvable> {
+        behavior
+            .get_min_solution(cost_model)
+            .map(|solution| solution.presses)
+    }
 
     #[test]
     fn test_read_behavior() {
         let behaviors_string =
             fs::read_to_string("test.txt").expect("Should have been able to read the file");
         let (_, behaviors) = read_input(&behaviors_string).unwrap();
-        let result = get_result(behaviors);
-        assert_eq!(result, 480)
+        let result = get_result(&behaviors, &CostModel::part1(), false);
+        assert_eq!(result, Ok(480))
+    }
+
+    #[test]
+    fn test_unit_conversion_correction() {
+        let behaviors_string =
+            fs::read_to_string("test.txt").expect("Should have been able to read the file");
+        let (_, behaviors) = read_input(&behaviors_string).unwrap();
+        let result = get_result(&behaviors, &CostModel::part2(), false);
+        assert_eq!(result, Ok(875318608908))
+    }
+
+    #[test]
+    fn test_unsolvable_reasons() {
+        let part1 = CostModel::part1();
+        let unlimited = unlimited(vec![3, 1]);
+        assert_eq!(
+            get_presses(&behavior(&[(26, 66), (67, 21)], (12748, 12176)), &part1),
+            Err(Unsolvable::FractionalPresses)
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(1, 0), (0, 1)], (-1, 5)), &unlimited),
+            Err(Unsolvable::NegativePresses)
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(1, 0), (0, 1)], (101, 5)), &part1),
+            Err(Unsolvable::PressLimitExceeded)
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(1, 1), (2, 2)], (3, 4)), &unlimited),
+            Err(Unsolvable::OffLine)
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(2, 2), (4, 4)], (3, 3)), &unlimited),
+            Err(Unsolvable::NoIntegerCombination)
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(3, 3), (5, 5)], (7, 7)), &unlimited),
+            Err(Unsolvable::NoValidCombination)
+        );
+        assert_eq!(
+            get_presses(
+                &behavior(&[(1, 1), (-1, 0), (0, -1), (1, 0)], (7, 7)),
+                &CostModel {
+                    button_costs: vec![3, 1, 1, 1],
+                    ..unlimited
+                }
+            ),
+            Err(Unsolvable::UnboundedSearch)
+        );
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        // A costs 3 tokens for 3 steps, B costs 1 token for 1 step: B is cheaper per step.
+        assert_eq!(
+            behavior(&[(3, 6), (1, 2)], (10, 20)).get_min_solution(&unlimited(vec![3, 1])),
+            Ok(Solution {
+                presses: vec![0, 10],
+                tokens: 10
+            })
+        );
+        // A costs 3 tokens for 4 steps, B costs 1 token for 1 step: A is cheaper per step.
+        assert_eq!(
+            get_presses(
+                &behavior(&[(4, 4), (1, 1)], (10, 10)),
+                &unlimited(vec![3, 1])
+            ),
+            Ok(vec![2, 2])
+        );
+        assert_eq!(
+            get_presses(
+                &behavior(&[(4, 4), (1, 1)], (410, 410)),
+                &CostModel::part1()
+            ),
+            Ok(vec![100, 10])
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(0, 0), (0, 0)], (0, 0)), &unlimited(vec![3, 1])),
+            Ok(vec![0, 0])
+        );
+    }
+
+    #[test]
+    fn test_cost_model() {
+        let behavior = behavior(&[(4, 4), (1, 1)], (10, 10));
+        assert_eq!(
+            get_presses(&behavior, &unlimited(vec![5, 1])),
+            Ok(vec![0, 10])
+        );
+        assert_eq!(
+            get_presses(
+                &behavior,
+                &CostModel {
+                    button_costs: vec![5, 1],
+                    press_limit: Some(5),
+                    prize_offset: 0
+                }
+            ),
+            Ok(vec![2, 2])
+        );
+        assert_eq!(
+            get_presses(
+                &behavior,
+                &CostModel {
+                    button_costs: vec![3, 1],
+                    press_limit: None,
+                    prize_offset: 2
+                }
+            ),
+            Ok(vec![3, 0])
+        );
+    }
+
+    #[test]
+    fn test_large_offset() {
+        let cost_model = CostModel {
+            button_costs: vec![1, 0],
+            press_limit: None,
+            prize_offset: i64::MAX,
+        };
+        assert_eq!(
+            behavior(&[(1, 0), (0, 1)], (1, 1)).get_min_solution(&cost_model),
+            Ok(Solution {
+                presses: vec![1 << 63, 1 << 63],
+                tokens: 1 << 63
+            })
+        );
+    }
+
+    #[test]
+    fn test_extra_buttons() {
+        let cost_model = CostModel {
+            button_costs: vec![3, 1, 2],
+            press_limit: Some(100),
+            prize_offset: 0,
+        };
+        // The third button is a cheap A + B, so it replaces every B press and its A.
+        assert_eq!(
+            behavior(&[(94, 34), (22, 67), (116, 101)], (8400, 5400)).get_min_solution(&cost_model),
+            Ok(Solution {
+                presses: vec![40, 0, 40],
+                tokens: 200
+            })
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(2, 0), (0, 3), (1, 1)], (4, 7)), &cost_model),
+            Ok(vec![0, 1, 4])
+        );
+        assert_eq!(
+            get_presses(&behavior(&[(2, 0)], (4, 0)), &unlimited(vec![3])),
+            Ok(vec![2])
+        );
+        // Buttons that move backwards leave no bound on the extra presses.
+        assert_eq!(
+            get_presses(
+                &behavior(&[(1, 1), (-1, 0), (0, -1)], (7, 7)),
+                &unlimited(vec![3, 1, 1])
+            ),
+            Ok(vec![7, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_extra_buttons_without_limit() {
+        let cost_model = CostModel {
+            button_costs: vec![3, 1, 2],
+            ..CostModel::part2()
+        };
+        let solution = behavior(&[(26, 66), (67, 21), (93, 87)], (12748, 12176))
+            .get_min_solution(&cost_model)
+            .unwrap();
+        let presses = solution.presses.iter().map(|&presses| presses as i64);
+        let position = presses
+            .zip([(26, 66), (67, 21), (93, 87)])
+            .fold((0, 0), |(x, y), (presses, (dx, dy))| {
+                (x + presses * dx, y + presses * dy)
+            });
+        assert_eq!(position, (10000000012748, 10000000012176));
+        assert_eq!(
+            solution.tokens,
+            solution.presses[0] * 3 + solution.presses[1] + solution.presses[2] * 2
+        );
+        // A fourth button brings back the enumeration, which gives up past
+        // its budget instead of trying every press count.
+        assert_eq!(
+            get_presses(
+                &behavior(&[(1, 0), (0, 1), (1, 1), (2, 1)], (8400, 5400)),
+                &CostModel {
+                    button_costs: vec![3, 1, 2, 2],
+                    ..CostModel::part2()
+                }
+            ),
+            Err(Unsolvable::UnboundedSearch)
+        );
+    }
+
+    #[test]
+    fn test_three_buttons() {
+        let limited = |button_costs: Vec<u64>| CostModel {
+            button_costs,
+            press_limit: Some(12),
+            prize_offset: 0,
+        };
+        let get_tokens = |buttons: &[(i64, i64)], prize, button_costs| {
+            behavior(buttons, prize)
+                .get_min_solution(&limited(button_costs))
+                .map(|solution| solution.tokens)
+        };
+        assert_eq!(
+            get_tokens(&[(3, 1), (1, 2), (2, 2)], (11, 9), vec![3, 1, 1]),
+            Ok(7)
+        );
+        assert_eq!(
+            get_presses(
+                &behavior(&[(1, 2), (-1, 1), (2, -1)], (5, 4)),
+                &limited(vec![1, 2, 3])
+            ),
+            Ok(vec![2, 3, 3])
+        );
+        // The first two buttons are collinear, so the pair is picked elsewhere.
+        assert_eq!(
+            get_presses(
+                &behavior(&[(2, 2), (4, 4), (1, 3)], (7, 9)),
+                &limited(vec![1, 1, 5])
+            ),
+            Ok(vec![1, 1, 1])
+        );
+        // All three collinear: the enumeration takes over.
+        assert_eq!(
+            get_tokens(&[(1, 1), (2, 2), (3, 3)], (6, 6), vec![1, 1, 1]),
+            Ok(2)
+        );
+        assert!(get_tokens(&[(5, 0), (0, 5), (3, 3)], (4, 4), vec![1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_cost_count() {
+        let behaviors = [
+            behavior(&[(1, 0), (0, 1)], (1, 1)),
+            behavior(&[(1, 0), (0, 1), (1, 1)], (1, 1)),
+        ];
+        assert_eq!(
+            behaviors[1].get_min_solution(&unlimited(vec![3])),
+            Err(Unsolvable::CostCount {
+                buttons: 3,
+                costs: 1
+            })
+        );
+        assert_eq!(
+            get_result(&behaviors, &CostModel::part1(), false),
+            Err("Machine 2: the machine has 3 buttons but 2 costs were given".to_string())
+        );
+        assert_eq!(
+            get_result(&behaviors[..1], &CostModel::part1(), false),
+            Ok(4)
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let args = ["test.txt", "--costs", "5,2", "--no-limit", "--offset", "7"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "test.txt");
+        assert_eq!(
+            options.cost_model,
+            Some(CostModel {
+                button_costs: vec![5, 2],
+                press_limit: None,
+                prize_offset: 7
+            })
+        );
+        let options = Options::from_args(["test.txt".to_string()].into_iter()).unwrap();
+        assert_eq!(options.cost_model, None);
     }
 }
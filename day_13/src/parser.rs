@@ -1,10 +1,10 @@
-use super::behavior::Behavior;
+use super::behavior::{Behavior, Button};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{i64, multispace1},
-    multi::separated_list1,
-    sequence::tuple,
+    character::complete::{alpha1, i64, multispace1, newline},
+    multi::{many1, separated_list1},
+    sequence::{terminated, tuple},
     IResult,
 };
 
@@ -12,29 +12,22 @@ pub fn read_input(input: &str) -> IResult<&str, Vec<Behavior>> {
     separated_list1(multispace1, read_behavior)(input)
 }
 
+fn read_button(button: &str) -> IResult<&str, Button> {
+    let (input, (_, _, _, x, _, y)) = terminated(
+        tuple((tag("Button "), alpha1, tag(": X"), i64, tag(", Y"), i64)),
+        newline,
+    )(button)?;
+    Ok((input, Button { x, y }))
+}
+
 fn read_behavior(behavior: &str) -> IResult<&str, Behavior> {
-    let (input, (_, x_a, _, y_a, _, x_b, _, y_b, _, x_prize, _, y_prize)) = tuple((
-        tag("Button A: X+"),
-        i64,
-        tag(", Y+"),
-        i64,
-        tag("\nButton B: X+"),
-        i64,
-        tag(", Y+"),
-        i64,
-        tag("\nPrize: X="),
-        i64,
-        tag(", Y="),
-        i64,
-    ))(behavior)?;
+    let (input, (buttons, _, x_prize, _, y_prize)) =
+        tuple((many1(read_button), tag("Prize: X="), i64, tag(", Y="), i64))(behavior)?;
 
     Ok((
         input,
         Behavior {
-            x_a,
-            y_a,
-            x_b,
-            y_b,
+            buttons,
             x_prize,
             y_prize,
         },
@@ -52,10 +45,7 @@ pub mod tests {
             Ok((
                 "",
                 Behavior {
-                    x_a: 94,
-                    y_a: 34,
-                    x_b: 22,
-                    y_b: 67,
+                    buttons: vec![Button { x: 94, y: 34 }, Button { x: 22, y: 67 }],
                     x_prize: 8400,
                     y_prize: 5400,
                 }
@@ -75,18 +65,12 @@ pub mod tests {
                 "",
                 vec![
                     Behavior {
-                        x_a: 94,
-                        y_a: 34,
-                        x_b: 22,
-                        y_b: 67,
+                        buttons: vec![Button { x: 94, y: 34 }, Button { x: 22, y: 67 }],
                         x_prize: 8400,
                         y_prize: 5400,
                     },
                     Behavior {
-                        x_a: 1,
-                        y_a: 2,
-                        x_b: 3,
-                        y_b: 4,
+                        buttons: vec![Button { x: 1, y: 2 }, Button { x: 3, y: 4 }],
                         x_prize: 5,
                         y_prize: 6,
                     },
@@ -94,4 +78,25 @@ pub mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_read_extra_buttons() {
+        assert_eq!(
+            read_behavior(
+                "Button A: X+1, Y-2\nButton B: X+3, Y+4\nButton C: X-5, Y+6\nPrize: X=7, Y=8"
+            ),
+            Ok((
+                "",
+                Behavior {
+                    buttons: vec![
+                        Button { x: 1, y: -2 },
+                        Button { x: 3, y: 4 },
+                        Button { x: -5, y: 6 }
+                    ],
+                    x_prize: 7,
+                    y_prize: 8,
+                }
+            ))
+        );
+    }
 }
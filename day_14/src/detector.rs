@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use super::robot::Position;
+use super::swarm::{gcd, RobotSwarm};
+
+const ENTROPY_BLOCK_SIZE: usize = 8;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Detector {
+    Variance,
+    Entropy,
+    LongestRun,
+}
+
+impl Detector {
+    pub fn new(name: &str) -> Option<Self> {
+        match name {
+            "variance" => Some(Detector::Variance),
+            "entropy" => Some(Detector::Entropy),
+            "run" => Some(Detector::LongestRun),
+            _ => None,
+        }
+    }
+
+    pub fn find_frame(&self, swarm: &RobotSwarm) -> Option<usize> {
+        match self {
+            Detector::Variance => find_frame_by_variance(swarm),
+            Detector::Entropy => (0..swarm.get_period())
+                .map(|steps| (steps, get_entropy(&swarm.at(steps).positions())))
+                .min_by(|(_, first), (_, second)| first.total_cmp(second))
+                .map(|(steps, _)| steps),
+            Detector::LongestRun => (0..swarm.get_period())
+                .map(|steps| (steps, get_longest_run(&swarm.at(steps).positions())))
+                .max_by_key(|&(steps, run)| (run, std::cmp::Reverse(steps)))
+                .map(|(steps, _)| steps),
+        }
+    }
+}
+
+fn get_variance(values: impl Iterator<Item = usize> + Clone) -> f64 {
+    let count = values.clone().count() as f64;
+    let mean = values.clone().sum::<usize>() as f64 / count;
+    values
+        .map(|value| (value as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count
+}
+
+fn find_frame_by_variance(swarm: &RobotSwarm) -> Option<usize> {
+    // Each axis repeats with its own period, so the step with the most
+    // compact x coordinates and the one with the most compact y coordinates
+    // can be found independently and combined.
+    let get_best_offset = |period: usize, axis: fn(&Position) -> usize| {
+        (0..period)
+            .map(|steps| {
+                let positions = swarm.at(steps).positions();
+                (steps, get_variance(positions.iter().map(axis)))
+            })
+            .min_by(|(_, first), (_, second)| first.total_cmp(second))
+            .map(|(steps, _)| steps)
+    };
+    let limits = swarm.limits;
+    let x_offset = get_best_offset(limits.x, |position| position.x)?;
+    let y_offset = get_best_offset(limits.y, |position| position.y)?;
+    chinese_remainder(x_offset, limits.x, y_offset, limits.y)
+}
+
+fn get_entropy(positions: &[Position]) -> f64 {
+    let mut blocks: HashMap<(usize, usize), usize> = HashMap::new();
+    for position in positions {
+        *blocks
+            .entry((
+                position.x / ENTROPY_BLOCK_SIZE,
+                position.y / ENTROPY_BLOCK_SIZE,
+            ))
+            .or_default() += 1;
+    }
+    let total = positions.len() as f64;
+    -blocks
+        .values()
+        .map(|&count| {
+            let probability = count as f64 / total;
+            probability * probability.log2()
+        })
+        .sum::<f64>()
+}
+
+fn get_longest_run(positions: &[Position]) -> usize {
+    let mut positions = positions.to_vec();
+    positions.sort_by_key(|position| (position.y, position.x));
+    positions.dedup();
+    let mut longest = 0;
+    let mut current = 0;
+    for (index, position) in positions.iter().enumerate() {
+        let continues = index > 0 && {
+            let previous = positions[index - 1];
+            previous.y == position.y && previous.x + 1 == position.x
+        };
+        current = if continues { current + 1 } else { 1 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+fn chinese_remainder(
+    first_remainder: usize,
+    first_modulus: usize,
+    second_remainder: usize,
+    second_modulus: usize,
+) -> Option<usize> {
+    let divisor = gcd(first_modulus, second_modulus);
+    if first_remainder % divisor != second_remainder % divisor {
+        return None;
+    }
+    let period = first_modulus / divisor * second_modulus;
+    (first_remainder..period)
+        .step_by(first_modulus)
+        .find(|candidate| candidate % second_modulus == second_remainder)
+}
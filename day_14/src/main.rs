@@ -1,103 +1,219 @@
+mod detector;
 mod parser;
 mod robot;
-use std::{collections::HashMap, fs};
+mod swarm;
+use std::fs;
 
+use detector::Detector;
 use parser::read_input;
-use robot::{Position, Quandrant, Robot};
-
-fn get_result_part1(robots: &Vec<Robot>, limits: Position, steps: i32) -> u32 {
-    let mut robots = robots.clone();
-    let mut counter: HashMap<Quandrant, u32> = [
-        (Quandrant::First, 0),
-        (Quandrant::Second, 0),
-        (Quandrant::Third, 0),
-        (Quandrant::Fourth, 0),
-    ]
-    .into_iter()
-    .collect();
-
-    for robot in robots.iter_mut() {
-        robot.move_steps(steps, limits);
-        match robot.get_quadrant(limits) {
-            Some(Quandrant::First) => *counter.get_mut(&Quandrant::First).unwrap() += 1,
-            Some(Quandrant::Second) => *counter.get_mut(&Quandrant::Second).unwrap() += 1,
-            Some(Quandrant::Third) => *counter.get_mut(&Quandrant::Third).unwrap() += 1,
-            Some(Quandrant::Fourth) => *counter.get_mut(&Quandrant::Fourth).unwrap() += 1,
-            None => (),
-        }
-    }
-    counter.values().into_iter().product()
-}
+use robot::Position;
+use swarm::RobotSwarm;
 
-fn get_result_part2(robots: &Vec<Robot>, limits: Position) -> usize {
-    let mut robots = robots.clone();
-    for k in 1..100000 {
-        robots
-            .iter_mut()
-            .for_each(|robot| robot.move_steps(1, limits));
-
-        if robots
-            .iter()
-            .filter(|robot| {
-                robot
-                    .get_neighbour_positions(limits)
-                    .into_iter()
-                    .any(|neighbour_pos| robots.iter().any(|robot| robot.position == neighbour_pos))
-            })
-            .count() as f64
-            > robots.len() as f64 * 0.5
-        {
-            print_robots(&robots, limits);
-            return k;
-        }
-    }
-    0
+struct Options {
+    input_filename: String,
+    width: Option<usize>,
+    height: Option<usize>,
+    steps: i32,
+    detector: Detector,
 }
 
-fn print_robots(robots: &Vec<Robot>, limits: Position) {
-    for i in 0..limits.x {
-        for j in 0..limits.y {
-            if robots
-                .iter()
-                .any(|robot| robot.position == Position { x: i, y: j })
-            {
-                print!("X");
-            } else {
-                print!(".");
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut width = None;
+        let mut height = None;
+        let mut steps = 100;
+        let mut detector = Detector::Variance;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => width = Some(Self::parse_size(&arg, args.next())?),
+                "--height" => height = Some(Self::parse_size(&arg, args.next())?),
+                "--steps" => steps = Self::parse_value(&arg, args.next())?,
+                "--detector" => {
+                    detector = args
+                        .next()
+                        .and_then(|name| Detector::new(&name))
+                        .ok_or("Option --detector expects variance, entropy or run")?
+                }
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
             }
         }
-        print!("\n");
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            width,
+            height,
+            steps,
+            detector,
+        })
+    }
+
+    fn parse_value<T: std::str::FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+        value
+            .and_then(|value| value.parse().ok())
+            .ok_or(format!("Option {} expects a positive integer", option))
+    }
+
+    fn parse_size(option: &str, value: Option<String>) -> Result<usize, String> {
+        Self::parse_value(option, value.filter(|value| value != "0"))
     }
-    print!("\n");
+}
+
+fn get_result_part1(swarm: &RobotSwarm, steps: i32) -> u32 {
+    let mut swarm = swarm.clone();
+    swarm.step(steps);
+    swarm.get_safety_factor()
+}
+
+fn get_result_part2(swarm: &RobotSwarm, detector: Detector) -> Option<usize> {
+    let steps = detector.find_frame(swarm)?;
+    print!("{}", swarm.at(steps).render());
+    Some(steps)
 }
 
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!(
+            "Usage: day_14 <input> [--width 101] [--height 103] [--steps 100] [--detector variance|entropy|run]"
+        );
         std::process::exit(1)
     });
-    let robots_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let robots_string = fs::read_to_string(&options.input_filename)
+        .expect("Should have been able to read the file");
 
     let (_, robots) = read_input(&robots_string).unwrap();
-    let result_part1 = get_result_part1(&robots, Position { x: 101, y: 103 }, 100);
-    let result_part2 = get_result_part2(&robots, Position { x: 101, y: 103 });
+    let detected_limits = RobotSwarm::detect_limits(&robots);
+    let limits = Position {
+        x: options.width.unwrap_or(detected_limits.x),
+        y: options.height.unwrap_or(detected_limits.y),
+    };
+    let swarm = RobotSwarm::new(robots, limits).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(1)
+    });
 
+    let result_part1 = get_result_part1(&swarm, options.steps);
     println!("Result part 1: {}", result_part1);
-    println!("Result part 2: {}", result_part2);
+
+    match get_result_part2(&swarm, options.detector) {
+        Some(result_part2) => println!("Result part 2: {}", result_part2),
+        None => println!("Result part 2: no frame detected"),
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
 
     use super::*;
+    use robot::{Robot, Velocity};
+
+    fn read_example() -> RobotSwarm {
+        let robots_string =
+            fs::read_to_string("test.txt").expect("Should have been able to read the file");
+        let (_, robots) = read_input(&robots_string).unwrap();
+        let limits = RobotSwarm::detect_limits(&robots);
+        RobotSwarm::new(robots, limits).unwrap()
+    }
 
     #[test]
     fn test_part1() {
+        let swarm = read_example();
+        assert_eq!(swarm.limits, Position { x: 11, y: 7 });
+        let result = get_result_part1(&swarm, 100);
+        assert_eq!(result, 12);
+    }
+
+    #[test]
+    fn test_swarm() {
+        let swarm = read_example().at(100);
+        assert_eq!(swarm.count_quadrants(), [1, 3, 4, 1]);
+        let density = swarm.get_density_map();
+        assert_eq!(density[0], vec![0, 0, 0, 0, 0, 0, 2, 0, 0, 1, 0]);
+        assert_eq!(density.iter().flatten().sum::<usize>(), 12);
+        assert_eq!(read_example().get_period(), 77);
+    }
+
+    #[test]
+    fn test_options() {
+        let args = [
+            "test.txt",
+            "--width",
+            "11",
+            "--steps",
+            "5",
+            "--detector",
+            "run",
+        ];
+        let options = Options::from_args(args.map(String::from).into_iter()).unwrap();
+        assert_eq!(options.input_filename, "test.txt");
+        assert_eq!((options.width, options.height), (Some(11), None));
+        assert_eq!(options.steps, 5);
+        assert_eq!(options.detector, Detector::LongestRun);
+        for option in ["--width", "--height"] {
+            let args = ["test.txt", option, "0"].map(String::from);
+            assert_eq!(
+                Options::from_args(args.into_iter()).err(),
+                Some(format!("Option {} expects a positive integer", option))
+            );
+        }
+    }
+
+    #[test]
+    fn test_robot_outside_limits() {
         let robots_string =
             fs::read_to_string("test.txt").expect("Should have been able to read the file");
         let (_, robots) = read_input(&robots_string).unwrap();
-        let result = get_result_part1(&robots, Position { x: 11, y: 7 }, 100);
-        assert_eq!(result, 12);
+        assert_eq!(
+            RobotSwarm::new(robots, Position { x: 5, y: 5 }),
+            Err("Robot 2 starts at 6,3, outside the 5x5 area".to_string())
+        );
+    }
+
+    fn hidden_square(limits: Position, steps: usize) -> RobotSwarm {
+        // A filled 10x10 square at the given step, scattered at every other
+        // step. Velocities grow quadratically with the robot index, so that
+        // they do not line up into a lattice that repeats the square.
+        let mut robots = vec![];
+        for y in 40..50 {
+            for x in 30..40 {
+                let index = (y - 40) * 10 + (x - 30);
+                let velocity = Velocity {
+                    x: ((index * index * 7 + index * 3) % limits.x) as i32 - limits.x as i32 / 2,
+                    y: ((index * index * 5 + index * 11 + 2) % limits.y) as i32
+                        - limits.y as i32 / 2,
+                };
+                let mut robot = Robot {
+                    position: Position { x, y },
+                    velocity,
+                };
+                robot.move_steps(-(steps as i32), limits);
+                robots.push(robot);
+            }
+        }
+        RobotSwarm::new(robots, limits).unwrap()
+    }
+
+    #[test]
+    fn test_detectors() {
+        let swarm = hidden_square(Position { x: 101, y: 103 }, 6577);
+        for detector in [Detector::Variance, Detector::Entropy, Detector::LongestRun] {
+            assert_eq!(detector.find_frame(&swarm), Some(6577));
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let swarm = RobotSwarm::new(
+            vec![Robot {
+                position: Position { x: 2, y: 0 },
+                velocity: Velocity { x: 0, y: 0 },
+            }],
+            Position { x: 3, y: 2 },
+        )
+        .unwrap();
+        assert_eq!(swarm.render(), "..X\n...\n");
     }
 }
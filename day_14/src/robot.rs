@@ -26,27 +26,6 @@ impl Robot {
             Some(Quandrant::Fourth)
         }
     }
-
-    pub fn get_neighbour_positions(&self, limits: Position) -> Vec<Position> {
-        vec![
-            Position {
-                x: (self.position.x + 1).rem_euclid(limits.x),
-                y: self.position.y.rem_euclid(limits.y),
-            },
-            Position {
-                x: self.position.x.rem_euclid(limits.x),
-                y: (self.position.y + 1).rem_euclid(limits.y),
-            },
-            Position {
-                x: ((self.position.x as i32 - 1).rem_euclid(limits.x as i32)) as usize,
-                y: self.position.y.rem_euclid(limits.y),
-            },
-            Position {
-                x: self.position.x.rem_euclid(limits.x),
-                y: ((self.position.y as i32 - 1).rem_euclid(limits.y as i32)) as usize,
-            },
-        ]
-    }
 }
 
 #[derive(PartialEq, Eq, Hash, Debug)]
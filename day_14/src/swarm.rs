@@ -0,0 +1,120 @@
+use super::robot::{Position, Quandrant, Robot};
+
+const EXAMPLE_LIMITS: Position = Position { x: 11, y: 7 };
+const PUZZLE_LIMITS: Position = Position { x: 101, y: 103 };
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RobotSwarm {
+    pub robots: Vec<Robot>,
+    pub limits: Position,
+}
+
+impl RobotSwarm {
+    pub fn new(robots: Vec<Robot>, limits: Position) -> Result<Self, String> {
+        if let Some((index, robot)) = robots
+            .iter()
+            .enumerate()
+            .find(|(_, robot)| robot.position.x >= limits.x || robot.position.y >= limits.y)
+        {
+            return Err(format!(
+                "Robot {} starts at {},{}, outside the {}x{} area",
+                index + 1,
+                robot.position.x,
+                robot.position.y,
+                limits.x,
+                limits.y
+            ));
+        }
+        Ok(RobotSwarm { robots, limits })
+    }
+
+    pub fn detect_limits(robots: &[Robot]) -> Position {
+        if robots
+            .iter()
+            .all(|robot| robot.position.x < EXAMPLE_LIMITS.x && robot.position.y < EXAMPLE_LIMITS.y)
+        {
+            EXAMPLE_LIMITS
+        } else {
+            PUZZLE_LIMITS
+        }
+    }
+
+    pub fn step(&mut self, steps: i32) {
+        let limits = self.limits;
+        self.robots
+            .iter_mut()
+            .for_each(|robot| robot.move_steps(steps, limits));
+    }
+
+    pub fn at(&self, steps: usize) -> Self {
+        let mut swarm = self.clone();
+        swarm.step(steps as i32);
+        swarm
+    }
+
+    pub fn positions(&self) -> Vec<Position> {
+        self.robots.iter().map(|robot| robot.position).collect()
+    }
+
+    pub fn count_quadrants(&self) -> [u32; 4] {
+        let mut counter = [0; 4];
+        for robot in self.robots.iter() {
+            let quadrant = match robot.get_quadrant(self.limits) {
+                Some(Quandrant::First) => 0,
+                Some(Quandrant::Second) => 1,
+                Some(Quandrant::Third) => 2,
+                Some(Quandrant::Fourth) => 3,
+                None => continue,
+            };
+            counter[quadrant] += 1;
+        }
+        counter
+    }
+
+    pub fn get_safety_factor(&self) -> u32 {
+        self.count_quadrants().into_iter().product()
+    }
+
+    pub fn get_density_map(&self) -> Vec<Vec<usize>> {
+        let mut density = vec![vec![0; self.limits.x]; self.limits.y];
+        for robot in self.robots.iter() {
+            density[robot.position.y][robot.position.x] += 1;
+        }
+        density
+    }
+
+    pub fn get_period(&self) -> usize {
+        self.robots.iter().fold(1, |period, robot| {
+            let x_period = axis_period(robot.velocity.x, self.limits.x);
+            let y_period = axis_period(robot.velocity.y, self.limits.y);
+            lcm(period, lcm(x_period, y_period))
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut rendered = String::new();
+        for row in self.get_density_map() {
+            for count in row {
+                rendered.push(if count > 0 { 'X' } else { '.' });
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+fn axis_period(velocity: i32, limit: usize) -> usize {
+    limit / gcd(velocity.rem_euclid(limit as i32) as usize, limit)
+}
+
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
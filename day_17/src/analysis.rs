@@ -0,0 +1,268 @@
+use super::assembly::format_instruction;
+use nom::lib::std::fmt;
+
+// Registers are named after their value at the start of the iteration.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Expr {
+    A,
+    B,
+    C,
+    Const(u64),
+    Shr(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    Mod8(Box<Expr>),
+}
+
+impl Expr {
+    fn shr(value: Expr, shift: Expr) -> Expr {
+        match (value, shift) {
+            (Expr::Const(value), Expr::Const(shift)) => {
+                Expr::Const(value.checked_shr(shift as u32).unwrap_or(0))
+            }
+            (value, Expr::Const(0)) => value,
+            (Expr::Shr(value, inner), Expr::Const(shift)) if matches!(*inner, Expr::Const(_)) => {
+                let Expr::Const(inner) = *inner else {
+                    unreachable!()
+                };
+                Expr::shr(*value, Expr::Const(inner + shift))
+            }
+            (value, shift) => Expr::Shr(Box::new(value), Box::new(shift)),
+        }
+    }
+
+    fn xor(left: Expr, right: Expr) -> Expr {
+        match (left, right) {
+            (Expr::Const(left), Expr::Const(right)) => Expr::Const(left ^ right),
+            (value, Expr::Const(0)) | (Expr::Const(0), value) => value,
+            (Expr::Xor(value, inner), Expr::Const(constant))
+                if matches!(*inner, Expr::Const(_)) =>
+            {
+                let Expr::Const(inner) = *inner else {
+                    unreachable!()
+                };
+                Expr::xor(*value, Expr::Const(inner ^ constant))
+            }
+            (left, right) => Expr::Xor(Box::new(left), Box::new(right)),
+        }
+    }
+
+    fn mod8(value: Expr) -> Expr {
+        match value {
+            Expr::Const(value) => Expr::Const(value % 8),
+            Expr::Mod8(value) => Expr::Mod8(value),
+            value => Expr::Mod8(Box::new(value)),
+        }
+    }
+
+    pub fn evaluate(&self, a: u64, b: u64, c: u64) -> u64 {
+        match self {
+            Expr::A => a,
+            Expr::B => b,
+            Expr::C => c,
+            Expr::Const(value) => *value,
+            Expr::Shr(value, shift) => {
+                let shift = shift.evaluate(a, b, c);
+                u32::try_from(shift)
+                    .ok()
+                    .and_then(|shift| value.evaluate(a, b, c).checked_shr(shift))
+                    .unwrap_or(0)
+            }
+            Expr::Xor(left, right) => left.evaluate(a, b, c) ^ right.evaluate(a, b, c),
+            Expr::Mod8(value) => value.evaluate(a, b, c) % 8,
+        }
+    }
+
+    pub fn uses_initial_b_or_c(&self) -> bool {
+        match self {
+            Expr::B | Expr::C => true,
+            Expr::A | Expr::Const(_) => false,
+            Expr::Shr(left, right) | Expr::Xor(left, right) => {
+                left.uses_initial_b_or_c() || right.uses_initial_b_or_c()
+            }
+            Expr::Mod8(value) => value.uses_initial_b_or_c(),
+        }
+    }
+
+    fn get_max_value(&self) -> Option<u64> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            Expr::Mod8(_) => Some(7),
+            Expr::Xor(left, right) => {
+                let max = left.get_max_value()?.max(right.get_max_value()?);
+                Some(u64::MAX >> max.leading_zeros())
+            }
+            Expr::Shr(value, _) => value.get_max_value(),
+            Expr::A | Expr::B | Expr::C => None,
+        }
+    }
+
+    // Number of low bits of A that determine the low `bits` bits of the
+    // expression, or None when that number is unbounded.
+    pub fn get_a_bits(&self, bits: u32) -> Option<u32> {
+        match self {
+            Expr::A => Some(bits),
+            Expr::B | Expr::C | Expr::Const(_) => Some(0),
+            Expr::Xor(left, right) => Some(left.get_a_bits(bits)?.max(right.get_a_bits(bits)?)),
+            Expr::Mod8(value) => value.get_a_bits(bits.min(3)),
+            Expr::Shr(value, shift) => {
+                let max_shift = u32::try_from(shift.get_max_value()?).ok()?;
+                let shift_bits = u64::BITS - (max_shift as u64).leading_zeros();
+                Some(
+                    value
+                        .get_a_bits(bits.checked_add(max_shift)?)?
+                        .max(shift.get_a_bits(shift_bits)?),
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::A => write!(f, "A"),
+            Expr::B => write!(f, "B"),
+            Expr::C => write!(f, "C"),
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Shr(value, shift) => write!(f, "({} >> {})", value, shift),
+            Expr::Xor(left, right) => write!(f, "({} ^ {})", left, right),
+            Expr::Mod8(value) => write!(f, "({} % 8)", value),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Analysis {
+    pub outputs: Vec<Expr>,
+    pub a_shift: Option<u32>,
+}
+
+impl Analysis {
+    // Executes the body of the loop once, from address 0 up to the final
+    // `jnz 0`, keeping every register as an expression of its initial value.
+    pub fn new(program: &[u32]) -> Result<Self, String> {
+        if !program.len().is_multiple_of(2) {
+            return Err("The program has a dangling word".to_string());
+        }
+        let body = match program {
+            [body @ .., 3, 0] => body,
+            _ => program,
+        };
+        let (mut a, mut b, mut c) = (Expr::A, Expr::B, Expr::C);
+        let mut outputs = vec![];
+        let mut a_shift = Some(0);
+        for (index, pair) in body.chunks(2).enumerate() {
+            let (opcode, operand) = (pair[0], pair[1]);
+            let combo = || match operand {
+                0..=3 => Ok(Expr::Const(operand as u64)),
+                4 => Ok(a.clone()),
+                5 => Ok(b.clone()),
+                6 => Ok(c.clone()),
+                _ => Err(format!(
+                    "Address {}: {} has a reserved operand",
+                    index * 2,
+                    format_instruction(opcode, operand)
+                )),
+            };
+            match opcode {
+                0 => {
+                    a_shift = match (a_shift, operand) {
+                        (Some(shift), 0..=3) => Some(shift + operand),
+                        _ => None,
+                    };
+                    a = Expr::shr(a.clone(), combo()?);
+                }
+                1 => b = Expr::xor(b, Expr::Const(operand as u64)),
+                2 => b = Expr::mod8(combo()?),
+                4 => b = Expr::xor(b, c.clone()),
+                5 => outputs.push(Expr::mod8(combo()?)),
+                6 => b = Expr::shr(a.clone(), combo()?),
+                7 => c = Expr::shr(a.clone(), combo()?),
+                _ => {
+                    return Err(format!(
+                        "Address {}: {} is not supported inside the loop",
+                        index * 2,
+                        format_instruction(opcode, operand)
+                    ))
+                }
+            }
+        }
+        let is_loop = body.len() < program.len();
+        Ok(Analysis {
+            outputs,
+            a_shift: a_shift.filter(|&shift| is_loop && shift > 0),
+        })
+    }
+
+    pub fn get_summary(&self) -> String {
+        let mut summary = String::new();
+        match self.a_shift {
+            Some(shift) => summary.push_str(&format!(
+                "Loop: A >>= {} each iteration, jnz 0 at the end\n",
+                shift
+            )),
+            None => {
+                summary.push_str("No canonical loop: the outputs below are for a single pass\n")
+            }
+        }
+        for (index, output) in self.outputs.iter().enumerate() {
+            summary.push_str(&format!("out[{}] = {}\n", index, output));
+            let dependency = match output.get_a_bits(3) {
+                Some(bits) => format!("depends on the low {} bits of A", bits),
+                None => "depends on all the bits of A".to_string(),
+            };
+            summary.push_str(&format!("    {}", dependency));
+            if output.uses_initial_b_or_c() {
+                summary.push_str(", and on B or C from the previous iteration");
+            }
+            summary.push('\n');
+        }
+        if let (Some(shift), Some(bits)) = (
+            self.a_shift,
+            self.outputs
+                .iter()
+                .map(|output| output.get_a_bits(3))
+                .collect::<Option<Vec<u32>>>()
+                .and_then(|bits| bits.into_iter().max()),
+        ) {
+            summary.push_str(&format!(
+                "Iteration i reads bits {}*i to {}*i+{} of the initial A\n",
+                shift,
+                shift,
+                bits.max(1) - 1
+            ));
+        }
+        summary
+    }
+
+    // Rebuilds A from the last output backwards, a few bits per iteration,
+    // so that the program prints `expected`. Only canonical single-output
+    // loops are supported.
+    pub fn find_a_for_output(&self, expected: &[u32]) -> Option<u64> {
+        let shift = self.a_shift?;
+        let [output] = self.outputs.as_slice() else {
+            return None;
+        };
+        if output.uses_initial_b_or_c() || expected.is_empty() {
+            return None;
+        }
+        find_a_prefix(output, shift, expected, expected.len() - 1, 0)
+    }
+}
+
+fn find_a_prefix(output: &Expr, shift: u32, expected: &[u32], index: usize, a: u64) -> Option<u64> {
+    (0..1 << shift).find_map(|digit| {
+        let candidate = a.checked_mul(1 << shift)? | digit;
+        // Every iteration after the first one is only reached when A is not 0.
+        if (index > 0 && candidate == 0)
+            || output.evaluate(candidate, 0, 0) != expected[index] as u64
+        {
+            return None;
+        }
+        if index == 0 {
+            Some(candidate)
+        } else {
+            find_a_prefix(output, shift, expected, index - 1, candidate)
+        }
+    })
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+const MNEMONICS: [&str; 8] = ["adv", "bxl", "bst", "jnz", "bxc", "out", "bdv", "cdv"];
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum OperandKind {
+    Combo,
+    Literal,
+    Jump,
+    Ignored,
+}
+
+fn get_operand_kind(opcode: u32) -> OperandKind {
+    match opcode {
+        1 => OperandKind::Literal,
+        3 => OperandKind::Jump,
+        4 => OperandKind::Ignored,
+        _ => OperandKind::Combo,
+    }
+}
+
+fn get_label(labels: &BTreeMap<u32, usize>, address: u32) -> String {
+    format!("L{}", labels[&address])
+}
+
+pub fn disassemble(program: &[u32]) -> String {
+    let labels: BTreeMap<u32, usize> = program
+        .chunks(2)
+        .filter(|pair| pair.len() == 2 && pair[0] == 3)
+        .map(|pair| pair[1])
+        .filter(|&target| target % 2 == 0 && (target as usize) < program.len())
+        .collect::<std::collections::BTreeSet<u32>>()
+        .into_iter()
+        .enumerate()
+        .map(|(index, target)| (target, index))
+        .collect();
+
+    let mut text = String::new();
+    for (index, pair) in program.chunks(2).enumerate() {
+        let address = (index * 2) as u32;
+        if labels.contains_key(&address) {
+            text.push_str(&format!("{}:\n", get_label(&labels, address)));
+        }
+        let line = match *pair {
+            [3, operand] if labels.contains_key(&operand) => {
+                format!("{} {}", MNEMONICS[3], get_label(&labels, operand))
+            }
+            [opcode, operand] => format_instruction(opcode, operand),
+            _ => format!(
+                ".word {}",
+                pair.iter()
+                    .map(|word| word.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        };
+        text.push_str(&format!("    {}\n", line));
+    }
+    text
+}
+
+pub fn format_instruction(opcode: u32, operand: u32) -> String {
+    if opcode >= 8 {
+        return format!(".word {}, {}", opcode, operand);
+    }
+    let mnemonic = MNEMONICS[opcode as usize];
+    match get_operand_kind(opcode) {
+        OperandKind::Combo => format!("{} {}", mnemonic, get_combo_name(operand)),
+        OperandKind::Literal | OperandKind::Jump => format!("{} {}", mnemonic, operand),
+        OperandKind::Ignored if operand == 0 => mnemonic.to_string(),
+        OperandKind::Ignored => format!("{} {}", mnemonic, operand),
+    }
+}
+
+fn get_combo_name(operand: u32) -> String {
+    match operand {
+        4 => "A".to_string(),
+        5 => "B".to_string(),
+        6 => "C".to_string(),
+        _ => operand.to_string(),
+    }
+}
+
+pub fn assemble(text: &str) -> Result<Vec<u32>, String> {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.split(';').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+
+    let mut labels: BTreeMap<&str, u32> = BTreeMap::new();
+    let mut address = 0;
+    for &(number, line) in lines.iter() {
+        if let Some(label) = line.strip_suffix(':') {
+            if labels.insert(label.trim(), address).is_some() {
+                return Err(format!("Line {}: duplicated label {}", number, label));
+            }
+        } else if let Some(words) = line.strip_prefix(".word") {
+            address += words.split(',').count() as u32;
+        } else {
+            address += 2;
+        }
+    }
+
+    let mut program = vec![];
+    for (number, line) in lines {
+        if line.ends_with(':') {
+            continue;
+        }
+        if let Some(words) = line.strip_prefix(".word") {
+            for word in words.split(',') {
+                let word = word.trim();
+                program.push(
+                    word.parse()
+                        .map_err(|_| format!("Line {}: {} is not a number", number, word))?,
+                );
+            }
+            continue;
+        }
+
+        let (mnemonic, operand) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, None), |(mnemonic, operand)| {
+                (mnemonic, Some(operand.trim()))
+            });
+        let opcode = MNEMONICS
+            .iter()
+            .position(|&candidate| candidate == mnemonic)
+            .ok_or(format!("Line {}: unknown mnemonic {}", number, mnemonic))?
+            as u32;
+        let operand = match (get_operand_kind(opcode), operand) {
+            (OperandKind::Ignored, None) => 0,
+            (_, None) => return Err(format!("Line {}: {} needs an operand", number, mnemonic)),
+            (OperandKind::Combo, Some("A")) => 4,
+            (OperandKind::Combo, Some("B")) => 5,
+            (OperandKind::Combo, Some("C")) => 6,
+            (OperandKind::Jump, Some(label)) if labels.contains_key(label) => Some(labels[label])
+                .filter(|&address| address < 8)
+                .ok_or(format!(
+                    "Line {}: label {} is at address {}, out of reach of a 3-bit jump",
+                    number, label, labels[label]
+                ))?,
+            (_, Some(operand)) => parse_number(operand, number)?,
+        };
+        program.push(opcode);
+        program.push(operand);
+    }
+    Ok(program)
+}
+
+fn parse_number(word: &str, number: usize) -> Result<u32, String> {
+    word.parse::<u32>()
+        .ok()
+        .filter(|&value| value < 8)
+        .ok_or(format!("Line {}: {} is not a 3-bit number", number, word))
+}
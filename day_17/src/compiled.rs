@@ -0,0 +1,143 @@
+use super::computer::Fault;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Operand {
+    Literal(u64),
+    A,
+    B,
+    C,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+enum Instruction {
+    DivideA(Operand),
+    DivideB(Operand),
+    DivideC(Operand),
+    // Divisions by a literal power of two are folded into plain shifts.
+    ShiftA(u32),
+    ShiftB(u32),
+    ShiftC(u32),
+    XorB(u64),
+    SetB(Operand),
+    Jump(usize),
+    XorBC,
+    Out(Operand),
+    Trap(Fault),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CompiledProgram {
+    instructions: Vec<Instruction>,
+    pub max_cycles: Option<usize>,
+}
+
+impl CompiledProgram {
+    pub fn new(program: &[u32]) -> Self {
+        // Jumps may land on any word, so every address gets its own decoded
+        // instruction, including the ones that are normally operands.
+        let instructions = program
+            .windows(2)
+            .enumerate()
+            .map(|(address, pair)| compile_instruction(address, pair[0], pair[1]))
+            .collect();
+        CompiledProgram {
+            instructions,
+            max_cycles: None,
+        }
+    }
+
+    pub fn run(&self, a: u64, b: u64, c: u64) -> Result<Vec<u64>, Fault> {
+        let (mut a, mut b, mut c) = (a, b, c);
+        let mut output = vec![];
+        let mut pointer = 0;
+        let mut cycles = 0;
+        while let Some(&instruction) = self.instructions.get(pointer) {
+            if let Some(limit) = self.max_cycles {
+                if cycles >= limit {
+                    return Err(Fault::CycleLimitExceeded {
+                        address: pointer,
+                        limit,
+                    });
+                }
+            }
+            let read = |operand| match operand {
+                Operand::Literal(value) => value,
+                Operand::A => a,
+                Operand::B => b,
+                Operand::C => c,
+            };
+            let divide = |operand| {
+                let shift = read(operand);
+                u32::try_from(shift)
+                    .ok()
+                    .and_then(|shift| a.checked_shr(shift))
+                    .ok_or(Fault::ShiftOverflow {
+                        address: pointer,
+                        shift,
+                    })
+            };
+            let mut next_pointer = pointer + 2;
+            match instruction {
+                Instruction::DivideA(operand) => a = divide(operand)?,
+                Instruction::DivideB(operand) => b = divide(operand)?,
+                Instruction::DivideC(operand) => c = divide(operand)?,
+                Instruction::ShiftA(shift) => a >>= shift,
+                Instruction::ShiftB(shift) => b = a >> shift,
+                Instruction::ShiftC(shift) => c = a >> shift,
+                Instruction::XorB(value) => b ^= value,
+                Instruction::SetB(operand) => b = read(operand) % 8,
+                Instruction::Jump(target) => {
+                    if a != 0 {
+                        next_pointer = target;
+                    }
+                }
+                Instruction::XorBC => b ^= c,
+                Instruction::Out(operand) => output.push(read(operand) % 8),
+                Instruction::Trap(fault) => return Err(fault),
+            }
+            pointer = next_pointer;
+            cycles += 1;
+        }
+        Ok(output)
+    }
+}
+
+fn compile_operand(address: usize, operand: u32) -> Result<Operand, Fault> {
+    match operand {
+        0..=3 => Ok(Operand::Literal(operand as u64)),
+        4 => Ok(Operand::A),
+        5 => Ok(Operand::B),
+        6 => Ok(Operand::C),
+        _ => Err(Fault::ReservedOperand { address, operand }),
+    }
+}
+
+fn compile_division(
+    address: usize,
+    operand: u32,
+    divide: fn(Operand) -> Instruction,
+    shift: fn(u32) -> Instruction,
+) -> Result<Instruction, Fault> {
+    Ok(match compile_operand(address, operand)? {
+        Operand::Literal(value) => shift(value as u32),
+        operand => divide(operand),
+    })
+}
+
+fn compile_instruction(address: usize, opcode: u32, operand: u32) -> Instruction {
+    let instruction = match opcode {
+        0 => compile_division(address, operand, Instruction::DivideA, Instruction::ShiftA),
+        1 => Ok(Instruction::XorB(operand as u64)),
+        2 => compile_operand(address, operand).map(|operand| match operand {
+            Operand::Literal(value) => Instruction::SetB(Operand::Literal(value % 8)),
+            operand => Instruction::SetB(operand),
+        }),
+        3 => Ok(Instruction::Jump(operand as usize)),
+        4 => Ok(Instruction::XorBC),
+        5 => compile_operand(address, operand).map(Instruction::Out),
+        6 => compile_division(address, operand, Instruction::DivideB, Instruction::ShiftB),
+        7 => compile_division(address, operand, Instruction::DivideC, Instruction::ShiftC),
+        _ => Err(Fault::InvalidOpcode { address, opcode }),
+    };
+    instruction.unwrap_or_else(Instruction::Trap)
+}
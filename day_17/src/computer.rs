@@ -1,16 +1,102 @@
+use super::assembly::format_instruction;
+use nom::lib::std::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Registers {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A={} B={} C={}", self.a, self.b, self.c)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Step {
+    pub address: usize,
+    pub opcode: u32,
+    pub operand: u32,
+    pub before: Registers,
+    pub after: Registers,
+    pub output: Option<u64>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>4}: {:<8} {} -> {}",
+            self.address,
+            format_instruction(self.opcode, self.operand),
+            self.before,
+            self.after
+        )?;
+        if let Some(output) = self.output {
+            write!(f, " out {}", output)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Fault {
+    ReservedOperand { address: usize, operand: u32 },
+    InvalidOpcode { address: usize, opcode: u32 },
+    ShiftOverflow { address: usize, shift: u64 },
+    CycleLimitExceeded { address: usize, limit: usize },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fault::ReservedOperand { address, operand } => {
+                write!(
+                    f,
+                    "reserved combo operand {} at address {}",
+                    operand, address
+                )
+            }
+            Fault::InvalidOpcode { address, opcode } => {
+                write!(f, "invalid opcode {} at address {}", opcode, address)
+            }
+            Fault::ShiftOverflow { address, shift } => {
+                write!(
+                    f,
+                    "division by 2^{} overflows at address {}",
+                    shift, address
+                )
+            }
+            Fault::CycleLimitExceeded { address, limit } => {
+                write!(
+                    f,
+                    "cycle limit of {} exceeded at address {}",
+                    limit, address
+                )
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Computer {
-    instruction_pointer: i32,
-    pub a_register: u32,
-    pub b_register: u32,
-    pub c_register: u32,
+    instruction_pointer: usize,
+    cycles: usize,
+    pub max_cycles: Option<usize>,
+    pub a_register: u64,
+    pub b_register: u64,
+    pub c_register: u64,
     program: Vec<u32>,
 }
 
 impl Computer {
-    pub fn new(a_register: u32, b_register: u32, c_register: u32, program: Vec<u32>) -> Self {
+    pub fn new(a_register: u64, b_register: u64, c_register: u64, program: Vec<u32>) -> Self {
         Computer {
             instruction_pointer: 0,
+            cycles: 0,
+            max_cycles: None,
             a_register,
             b_register,
             c_register,
@@ -18,49 +104,110 @@ impl Computer {
         }
     }
 
-    pub fn run(&mut self) -> Vec<u32> {
-        let mut result = vec![];
-        while self.instruction_pointer as usize + 1 < self.program.len() {
-            let pointer = self.instruction_pointer as usize;
-            let instruction = self.program[pointer];
-            let operand = self.program[pointer + 1];
-            if let Some(output) = self.run_instruction(instruction, operand) {
-                result.push(output);
+    pub fn get_program(&self) -> &[u32] {
+        &self.program
+    }
+
+    pub fn get_instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    pub fn get_cycles(&self) -> usize {
+        self.cycles
+    }
+
+    pub fn get_registers(&self) -> Registers {
+        Registers {
+            a: self.a_register,
+            b: self.b_register,
+            c: self.c_register,
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.instruction_pointer + 1 >= self.program.len()
+    }
+
+    pub fn step(&mut self) -> Result<Option<Step>, Fault> {
+        if self.is_halted() {
+            return Ok(None);
+        }
+        let address = self.instruction_pointer;
+        if let Some(limit) = self.max_cycles {
+            if self.cycles >= limit {
+                return Err(Fault::CycleLimitExceeded { address, limit });
             }
-            self.instruction_pointer += 2;
         }
-        result
+        let opcode = self.program[address];
+        let operand = self.program[address + 1];
+        let before = self.get_registers();
+        let output = self.run_instruction(address, opcode, operand)?;
+        self.cycles += 1;
+        Ok(Some(Step {
+            address,
+            opcode,
+            operand,
+            before,
+            after: self.get_registers(),
+            output,
+        }))
+    }
+
+    pub fn run(&mut self) -> Result<Vec<u64>, Fault> {
+        let mut result = vec![];
+        while let Some(step) = self.step()? {
+            result.extend(step.output);
+        }
+        Ok(result)
     }
 
-    fn combo_operand(&self, operand: u32) -> u32 {
+    fn combo_operand(&self, address: usize, operand: u32) -> Result<u64, Fault> {
         match operand {
-            0..=3 => operand,
-            4 => self.a_register,
-            5 => self.b_register,
-            6 => self.c_register,
-            7 => panic!("Combo operand 7 is reserved and will not appear in valid programs."),
-            _ => panic!("Combo operand ({operand}) must be in range 0..=7."),
+            0..=3 => Ok(operand as u64),
+            4 => Ok(self.a_register),
+            5 => Ok(self.b_register),
+            6 => Ok(self.c_register),
+            _ => Err(Fault::ReservedOperand { address, operand }),
         }
     }
 
-    fn run_instruction(&mut self, instruction: u32, operand: u32) -> Option<u32> {
+    fn divide_a(&self, address: usize, operand: u32) -> Result<u64, Fault> {
+        let shift = self.combo_operand(address, operand)?;
+        u32::try_from(shift)
+            .ok()
+            .and_then(|shift| self.a_register.checked_shr(shift))
+            .ok_or(Fault::ShiftOverflow { address, shift })
+    }
+
+    fn run_instruction(
+        &mut self,
+        address: usize,
+        instruction: u32,
+        operand: u32,
+    ) -> Result<Option<u64>, Fault> {
+        let mut output = None;
+        let mut next_pointer = address + 2;
         match instruction {
-            0 => self.a_register = self.a_register / 2_u32.pow(self.combo_operand(operand)),
-            1 => self.b_register ^= operand,
-            2 => self.b_register = self.combo_operand(operand) % 8,
+            0 => self.a_register = self.divide_a(address, operand)?,
+            1 => self.b_register ^= operand as u64,
+            2 => self.b_register = self.combo_operand(address, operand)? % 8,
             3 => {
                 if self.a_register != 0 {
-                    self.instruction_pointer = operand as i32 - 2;
+                    next_pointer = operand as usize;
                 }
             }
             4 => self.b_register ^= self.c_register,
-            5 => {
-                return Some(self.combo_operand(operand) % 8);
+            5 => output = Some(self.combo_operand(address, operand)? % 8),
+            6 => self.b_register = self.divide_a(address, operand)?,
+            7 => self.c_register = self.divide_a(address, operand)?,
+            _ => {
+                return Err(Fault::InvalidOpcode {
+                    address,
+                    opcode: instruction,
+                })
             }
-            6 => self.b_register = self.a_register / 2_u32.pow(self.combo_operand(operand)),
-            7 => self.c_register = self.a_register / 2_u32.pow(self.combo_operand(operand)),
-            _ => panic!("Instruction ({instruction}) must be in range 0..=7."),
         }
-        None
+        self.instruction_pointer = next_pointer;
+        Ok(output)
     }
 }
@@ -0,0 +1,141 @@
+use std::collections::BTreeSet;
+
+use super::computer::{Computer, Fault, Registers, Step};
+use nom::lib::std::fmt;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
+
+impl Register {
+    pub fn new(name: &str) -> Option<Self> {
+        match name {
+            "a" | "A" => Some(Register::A),
+            "b" | "B" => Some(Register::B),
+            "c" | "C" => Some(Register::C),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, registers: &Registers) -> u64 {
+        match self {
+            Register::A => registers.a,
+            Register::B => registers.b,
+            Register::C => registers.c,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::A => write!(f, "A"),
+            Register::B => write!(f, "B"),
+            Register::C => write!(f, "C"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Stop {
+    Stepped,
+    Halted,
+    Breakpoint(usize),
+    Watch {
+        register: Register,
+        before: u64,
+        after: u64,
+    },
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Stepped => write!(f, "stepped"),
+            Stop::Halted => write!(f, "halted"),
+            Stop::Breakpoint(address) => write!(f, "breakpoint at {}", address),
+            Stop::Watch {
+                register,
+                before,
+                after,
+            } => write!(
+                f,
+                "register {} changed from {} to {}",
+                register, before, after
+            ),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Debugger {
+    pub breakpoints: BTreeSet<usize>,
+    pub watches: BTreeSet<Register>,
+    pub tracing: bool,
+    pub trace: Vec<Step>,
+    pub output: Vec<u64>,
+    stopped_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Debugger::default()
+    }
+
+    pub fn step(&mut self, computer: &mut Computer) -> Result<Stop, Fault> {
+        let stop = self.execute(computer);
+        self.stopped_at = Some(computer.get_instruction_pointer());
+        stop
+    }
+
+    // Breakpoints stop before their instruction runs. The instruction the
+    // computer was stopped at runs first, so that resuming from a breakpoint
+    // does not stop at it again.
+    pub fn resume(&mut self, computer: &mut Computer) -> Result<Stop, Fault> {
+        let mut resumed_from = self.stopped_at.take();
+        let stop = loop {
+            let address = computer.get_instruction_pointer();
+            if resumed_from.take() != Some(address)
+                && !computer.is_halted()
+                && self.breakpoints.contains(&address)
+            {
+                break Ok(Stop::Breakpoint(address));
+            }
+            match self.execute(computer) {
+                Ok(Stop::Stepped) => continue,
+                stop => break stop,
+            }
+        };
+        self.stopped_at = Some(computer.get_instruction_pointer());
+        stop
+    }
+
+    fn execute(&mut self, computer: &mut Computer) -> Result<Stop, Fault> {
+        let Some(step) = computer.step()? else {
+            return Ok(Stop::Halted);
+        };
+        self.output.extend(step.output);
+        let changed_watch = self.watches.iter().find_map(|register| {
+            let (before, after) = (register.get(&step.before), register.get(&step.after));
+            (before != after).then_some(Stop::Watch {
+                register: *register,
+                before,
+                after,
+            })
+        });
+        if self.tracing {
+            self.trace.push(step);
+        }
+
+        Ok(if let Some(watch) = changed_watch {
+            watch
+        } else if computer.is_halted() {
+            Stop::Halted
+        } else {
+            Stop::Stepped
+        })
+    }
+}
@@ -1,34 +1,256 @@
+mod analysis;
+mod assembly;
+mod compiled;
 mod computer;
+mod debugger;
 mod parser;
 
-use computer::Computer;
+use analysis::Analysis;
+use assembly::{assemble, disassemble};
+use compiled::CompiledProgram;
+use computer::{Computer, Fault};
+use debugger::{Debugger, Register, Stop};
 use parser::read_input;
 use std::fs;
+use std::io::BufRead;
+use std::time::Instant;
 
-fn get_result_part1(computer: &mut Computer) -> String {
-    computer
-        .run()
+struct DebugOptions {
+    input_filename: String,
+    debugger: Debugger,
+    max_cycles: Option<usize>,
+    single_step: bool,
+}
+
+impl DebugOptions {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut debugger = Debugger::new();
+        let mut max_cycles = None;
+        let mut single_step = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--break" => {
+                    debugger.breakpoints.insert(
+                        args.next()
+                            .and_then(|address| address.parse().ok())
+                            .ok_or("Option --break expects an address")?,
+                    );
+                }
+                "--watch" => {
+                    debugger.watches.insert(
+                        args.next()
+                            .and_then(|name| Register::new(&name))
+                            .ok_or("Option --watch expects a, b or c")?,
+                    );
+                }
+                "--max-cycles" => {
+                    max_cycles = Some(
+                        args.next()
+                            .and_then(|limit| limit.parse().ok())
+                            .ok_or("Option --max-cycles expects a positive integer")?,
+                    )
+                }
+                "--trace" => debugger.tracing = true,
+                "--step" => single_step = true,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(DebugOptions {
+            input_filename,
+            debugger,
+            max_cycles,
+            single_step,
+        })
+    }
+}
+
+fn debug(computer: &mut Computer, debugger: &mut Debugger, single_step: bool) {
+    let mut commands = std::io::stdin().lock().lines();
+    let mut paused = single_step;
+    loop {
+        if paused {
+            println!(
+                "[{}] {}  (s)tep, (c)ontinue, (q)uit",
+                computer.get_instruction_pointer(),
+                computer.get_registers()
+            );
+            match commands.next().and_then(|line| line.ok()).as_deref() {
+                Some("c") => paused = false,
+                Some("q") | None => break,
+                _ => {}
+            }
+        }
+        let stop = if paused {
+            debugger.step(computer)
+        } else {
+            debugger.resume(computer)
+        };
+        match stop {
+            Ok(Stop::Halted) => {
+                println!("Halted after {} cycles", computer.get_cycles());
+                break;
+            }
+            Ok(Stop::Stepped) => {}
+            Ok(stop) => {
+                println!("Stopped: {}", stop);
+                paused = true;
+            }
+            Err(fault) => {
+                println!("Fault: {}", fault);
+                break;
+            }
+        }
+    }
+    for step in debugger.trace.iter() {
+        println!("{}", step);
+    }
+    println!(
+        "Output: {}",
+        debugger
+            .output
+            .iter()
+            .map(|output| output.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    );
+}
+
+fn get_result_part1(computer: &mut Computer) -> Result<String, Fault> {
+    Ok(computer
+        .run()?
         .into_iter()
         .map(|output| output.to_string())
         .collect::<Vec<String>>()
-        .join(",")
+        .join(","))
 }
 
-fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
+fn benchmark(computer: &Computer, runs: u64) -> Result<(), String> {
+    let program = computer.get_program();
+    let (b, c) = (computer.b_register, computer.c_register);
+
+    let start = Instant::now();
+    let mut interpreted = 0;
+    for a in 0..runs {
+        let mut candidate = Computer::new(a, b, c, program.to_vec());
+        interpreted += candidate.run().map_or(0, |output| output.len());
+    }
+    let interpreter_time = start.elapsed();
+
+    let start = Instant::now();
+    let compiled_program = CompiledProgram::new(program);
+    let mut compiled = 0;
+    for a in 0..runs {
+        compiled += compiled_program
+            .run(a, b, c)
+            .map_or(0, |output| output.len());
+    }
+    let compiled_time = start.elapsed();
+
+    if interpreted != compiled {
+        return Err(format!(
+            "The compiled program printed {} values but the interpreter printed {}",
+            compiled, interpreted
+        ));
+    }
+    println!("Interpreter: {:?} for {} runs", interpreter_time, runs);
+    println!("Compiled: {:?} for {} runs", compiled_time, runs);
+    println!(
+        "Speedup: {:.2}x",
+        interpreter_time.as_secs_f64() / compiled_time.as_secs_f64()
+    );
+    Ok(())
+}
+
+fn read_file(filename: Option<String>) -> String {
+    let filename = filename.unwrap_or_else(|| {
         eprintln!("Please, provide the input file as argument");
         std::process::exit(1)
     });
-    let computer_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
-    let (_, mut computer) = read_input(&computer_string).unwrap();
-    let result_part1 = get_result_part1(&mut computer);
-    println!("Result part 1: {}", result_part1);
+    fs::read_to_string(filename).expect("Should have been able to read the file")
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("disassemble") => {
+            let (_, computer) = read_input(&read_file(args.next())).unwrap();
+            print!("{}", disassemble(computer.get_program()));
+        }
+        Some("debug") => {
+            let options = DebugOptions::from_args(args).unwrap_or_else(|message| {
+                eprintln!("{}", message);
+                eprintln!(
+                    "Usage: day_17 debug <input> [--break 0]... [--watch a]... [--max-cycles 1000] [--trace] [--step]"
+                );
+                std::process::exit(1)
+            });
+            let (_, mut computer) = read_input(&read_file(Some(options.input_filename))).unwrap();
+            computer.max_cycles = options.max_cycles;
+            let mut debugger = options.debugger;
+            debug(&mut computer, &mut debugger, options.single_step);
+        }
+        Some("analyze") => {
+            let (_, computer) = read_input(&read_file(args.next())).unwrap();
+            match Analysis::new(computer.get_program()) {
+                Ok(analysis) => {
+                    print!("{}", analysis.get_summary());
+                    if let Some(a) = analysis.find_a_for_output(computer.get_program()) {
+                        println!("Smallest A printing the program itself: {}", a);
+                    }
+                }
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(1)
+                }
+            }
+        }
+        Some("bench") => {
+            let (_, computer) = read_input(&read_file(args.next())).unwrap();
+            let runs = args
+                .next()
+                .map(|runs| {
+                    runs.parse()
+                        .expect("The number of runs should be an integer")
+                })
+                .unwrap_or(1_000_000);
+            if let Err(message) = benchmark(&computer, runs) {
+                eprintln!("{}", message);
+                std::process::exit(1)
+            }
+        }
+        Some("assemble") => match assemble(&read_file(args.next())) {
+            Ok(program) => println!(
+                "Program: {}",
+                program
+                    .iter()
+                    .map(|word| word.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(1)
+            }
+        },
+        input_filename => {
+            let computer_string = read_file(input_filename.map(String::from));
+            let (_, mut computer) = read_input(&computer_string).unwrap();
+            match get_result_part1(&mut computer) {
+                Ok(result_part1) => println!("Result part 1: {}", result_part1),
+                Err(fault) => println!("Result part 1: {}", fault),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use analysis::Expr;
 
     #[test]
     fn test_part1_example() {
@@ -36,8 +258,8 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 729", "Register B: 0", "Register C: 0", "Program: 0,1,5,4,3,0"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let result = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        let result = get_result_part1(&mut computer).unwrap();
         assert_eq!(result, "4,6,3,5,6,3,5,2,1,0");
     }
 
@@ -47,8 +269,8 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 0", "Register B: 1", "Register C: 9", "Program: 2,6"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let _ = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        get_result_part1(&mut computer).unwrap();
         assert_eq!(computer.b_register, 1);
     }
 
@@ -58,8 +280,8 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 10", "Register B: 0", "Register C: 0", "Program: 5,0,5,1,5,4"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let result = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        let result = get_result_part1(&mut computer).unwrap();
         assert_eq!(result, "0,1,2");
     }
 
@@ -69,8 +291,8 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 2024", "Register B: 0", "Register C: 0", "Program: 0,1,5,4,3,0"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let result = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        let result = get_result_part1(&mut computer).unwrap();
         assert_eq!(result, "4,2,5,6,7,7,7,7,3,1,0");
         assert_eq!(computer.a_register, 0);
     }
@@ -81,8 +303,8 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 0", "Register B: 29", "Register C: 0", "Program: 1,7"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let _ = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        get_result_part1(&mut computer).unwrap();
         assert_eq!(computer.b_register, 26);
     }
 
@@ -92,8 +314,325 @@ pub mod tests {
             "{}\n{}\n{}\n\n{}",
             "Register A: 0", "Register B: 2024", "Register C: 43690", "Program: 4,0"
         );
-        let (_, mut computer) = read_input(&computer_string).unwrap();
-        let _ = get_result_part1(&mut computer);
+        let (_, mut computer) = read_input(computer_string).unwrap();
+        get_result_part1(&mut computer).unwrap();
         assert_eq!(computer.c_register, 43690);
     }
+
+    fn example_computer() -> Computer {
+        Computer::new(729, 0, 0, vec![0, 1, 5, 4, 3, 0])
+    }
+
+    #[test]
+    fn test_single_step() {
+        let mut computer = example_computer();
+        let step = computer.step().unwrap().unwrap();
+        assert_eq!((step.address, step.opcode, step.operand), (0, 0, 1));
+        assert_eq!((step.before.a, step.after.a), (729, 364));
+        assert_eq!(
+            step.to_string(),
+            "   0: adv 1    A=729 B=0 C=0 -> A=364 B=0 C=0"
+        );
+        let step = computer.step().unwrap().unwrap();
+        assert_eq!(step.output, Some(4));
+        assert_eq!(computer.get_instruction_pointer(), 4);
+    }
+
+    #[test]
+    fn test_breakpoints_and_watches() {
+        let mut computer = example_computer();
+        let mut debugger = Debugger::new();
+        debugger.breakpoints.insert(4);
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Breakpoint(4)));
+        assert_eq!(debugger.output, vec![4]);
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Breakpoint(4)));
+        assert_eq!(debugger.output, vec![4, 6]);
+
+        debugger.breakpoints.clear();
+        debugger.watches.insert(Register::A);
+        assert_eq!(
+            debugger.resume(&mut computer),
+            Ok(Stop::Watch {
+                register: Register::A,
+                before: 182,
+                after: 91
+            })
+        );
+        debugger.watches.clear();
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Halted));
+        assert_eq!(debugger.output, vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_cycle_limit_and_trace() {
+        let mut computer = Computer::new(1, 0, 0, vec![3, 0]);
+        computer.max_cycles = Some(5);
+        let mut debugger = Debugger::new();
+        debugger.tracing = true;
+        let fault = debugger.resume(&mut computer).unwrap_err();
+        assert_eq!(
+            fault,
+            Fault::CycleLimitExceeded {
+                address: 0,
+                limit: 5
+            }
+        );
+        assert_eq!(fault.to_string(), "cycle limit of 5 exceeded at address 0");
+        assert_eq!(debugger.trace.len(), 5);
+        assert_eq!(
+            debugger.trace[0].to_string(),
+            "   0: jnz 0    A=1 B=0 C=0 -> A=1 B=0 C=0"
+        );
+    }
+
+    #[test]
+    fn test_faults() {
+        assert_eq!(
+            Computer::new(0, 0, 0, vec![5, 4, 2, 7]).run(),
+            Err(Fault::ReservedOperand {
+                address: 2,
+                operand: 7
+            })
+        );
+        assert_eq!(
+            Computer::new(0, 0, 0, vec![1, 3, 9, 0]).run(),
+            Err(Fault::InvalidOpcode {
+                address: 2,
+                opcode: 9
+            })
+        );
+        let mut computer = Computer::new(1 << 40, 64, 0, vec![0, 5]);
+        let fault = computer.run().unwrap_err();
+        assert_eq!(
+            fault,
+            Fault::ShiftOverflow {
+                address: 0,
+                shift: 64
+            }
+        );
+        // A faulting instruction leaves the registers untouched.
+        assert_eq!(computer.a_register, 1 << 40);
+    }
+
+    #[test]
+    fn test_wide_registers() {
+        let mut computer = Computer::new(1 << 40, 0, 0, vec![6, 3, 5, 5]);
+        assert_eq!(computer.run(), Ok(vec![0]));
+        assert_eq!(computer.b_register, 1 << 37);
+        // Jumping to the very end halts instead of underflowing the pointer.
+        assert_eq!(Computer::new(1, 0, 0, vec![3, 4, 5, 4]).run(), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_compiled_program() {
+        let compiled_program = CompiledProgram::new(&[0, 1, 5, 4, 3, 0]);
+        assert_eq!(
+            compiled_program.run(729, 0, 0),
+            Ok(vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0])
+        );
+        assert_eq!(
+            CompiledProgram::new(&[0, 3, 5, 4, 3, 0]).run(117440, 0, 0),
+            Ok(vec![0, 3, 5, 4, 3, 0])
+        );
+        let mut looping = CompiledProgram::new(&[3, 0]);
+        looping.max_cycles = Some(3);
+        assert_eq!(
+            looping.run(1, 0, 0),
+            Err(Fault::CycleLimitExceeded {
+                address: 0,
+                limit: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_compiled_matches_interpreter() {
+        let programs: [&[u32]; 14] = [
+            &[0, 1, 5, 4, 3, 0],
+            &[0, 3, 5, 4, 3, 0],
+            &[2, 4, 1, 5, 7, 5, 0, 3, 4, 1, 1, 6, 5, 5, 3, 0],
+            &[2, 6],
+            &[5, 0, 5, 1, 5, 4],
+            &[1, 7, 5, 5],
+            &[4, 0, 5, 5],
+            &[6, 2, 7, 5, 5, 6, 5, 7],
+            // A reserved combo operand and an invalid opcode.
+            &[5, 4, 2, 7],
+            &[5, 4, 8, 0],
+            // Odd length, and a jump into the middle of an instruction.
+            &[0, 1, 5],
+            &[3, 3, 5, 4, 0, 3],
+            // A shift by a register that does not fit, and an endless loop.
+            &[0, 6, 5, 4],
+            &[3, 0],
+        ];
+        for program in programs {
+            for (a, b, c) in [
+                (0, 0, 0),
+                (10, 29, 9),
+                (2024, 2024, 43690),
+                (1 << 40, 7, 100),
+            ] {
+                let mut computer = Computer::new(a, b, c, program.to_vec());
+                computer.max_cycles = Some(200);
+                let mut compiled_program = CompiledProgram::new(program);
+                compiled_program.max_cycles = Some(200);
+                assert_eq!(
+                    compiled_program.run(a, b, c),
+                    computer.run(),
+                    "program {:?} with registers {}, {}, {}",
+                    program,
+                    a,
+                    b,
+                    c
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_analysis() {
+        let program = vec![2, 4, 1, 5, 7, 5, 0, 3, 4, 1, 1, 6, 5, 5, 3, 0];
+        let analysis = Analysis::new(&program).unwrap();
+        assert_eq!(analysis.a_shift, Some(3));
+        assert_eq!(analysis.outputs.len(), 1);
+        assert_eq!(
+            analysis.outputs[0].to_string(),
+            "(((((A % 8) ^ 5) ^ (A >> ((A % 8) ^ 5))) ^ 6) % 8)"
+        );
+        assert_eq!(analysis.outputs[0].get_a_bits(3), Some(10));
+        assert_eq!(
+            analysis.get_summary(),
+            [
+                "Loop: A >>= 3 each iteration, jnz 0 at the end",
+                "out[0] = (((((A % 8) ^ 5) ^ (A >> ((A % 8) ^ 5))) ^ 6) % 8)",
+                "    depends on the low 10 bits of A",
+                "Iteration i reads bits 3*i to 3*i+9 of the initial A",
+                ""
+            ]
+            .join("\n")
+        );
+        for a in [0, 1, 7, 729, 117440, 35184372088831] {
+            let mut computer = Computer::new(a, 0, 0, program.clone());
+            let first_output = computer.run().unwrap()[0];
+            assert_eq!(analysis.outputs[0].evaluate(a, 0, 0), first_output);
+        }
+        let a = analysis.find_a_for_output(&program).unwrap();
+        assert_eq!(
+            Computer::new(a, 0, 0, program.clone()).run(),
+            Ok(program.iter().map(|&word| word as u64).collect())
+        );
+        let example = [0, 3, 5, 4, 3, 0];
+        let a = Analysis::new(&example).unwrap().find_a_for_output(&example);
+        assert_eq!(a, Some(117440));
+    }
+
+    #[test]
+    fn test_analysis_structure() {
+        let analysis = Analysis::new(&[0, 3, 5, 4, 3, 0]).unwrap();
+        assert_eq!(analysis.a_shift, Some(3));
+        assert_eq!(
+            analysis.outputs,
+            vec![Expr::Mod8(Box::new(Expr::Shr(
+                Box::new(Expr::A),
+                Box::new(Expr::Const(3))
+            )))]
+        );
+        // A straight-line program, and one whose output depends on B.
+        assert_eq!(Analysis::new(&[0, 1, 5, 4]).unwrap().a_shift, None);
+        let analysis = Analysis::new(&[4, 0, 5, 5, 0, 1, 3, 0]).unwrap();
+        assert_eq!(analysis.outputs[0].to_string(), "((B ^ C) % 8)");
+        assert!(analysis.outputs[0].uses_initial_b_or_c());
+        assert!(Analysis::new(&[3, 2, 5, 4, 3, 0]).is_err());
+        assert!(Analysis::new(&[5, 7, 3, 0]).is_err());
+    }
+
+    #[test]
+    fn test_break_at_start() {
+        let args = ["test.txt", "--break", "0"];
+        let mut debugger = DebugOptions::from_args(args.map(String::from).into_iter())
+            .unwrap()
+            .debugger;
+        let mut computer = example_computer();
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Breakpoint(0)));
+        assert_eq!(computer.get_cycles(), 0);
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Breakpoint(0)));
+        assert_eq!(debugger.output, vec![4]);
+        // Stepping onto a breakpoint and continuing runs its instruction.
+        assert_eq!(debugger.step(&mut computer), Ok(Stop::Stepped));
+        assert_eq!(debugger.step(&mut computer), Ok(Stop::Stepped));
+        assert_eq!(debugger.step(&mut computer), Ok(Stop::Stepped));
+        assert_eq!(computer.get_instruction_pointer(), 0);
+        assert_eq!(debugger.resume(&mut computer), Ok(Stop::Breakpoint(0)));
+        assert_eq!(debugger.output, vec![4, 6, 3]);
+    }
+
+    #[test]
+    fn test_debug_options() {
+        let args = [
+            "test.txt",
+            "--break",
+            "4",
+            "--watch",
+            "b",
+            "--max-cycles",
+            "10",
+            "--trace",
+        ];
+        let options = DebugOptions::from_args(args.map(String::from).into_iter()).unwrap();
+        assert_eq!(options.input_filename, "test.txt");
+        assert!(options.debugger.breakpoints.contains(&4));
+        assert!(options.debugger.watches.contains(&Register::B));
+        assert_eq!(options.max_cycles, Some(10));
+        assert!(options.debugger.tracing && !options.single_step);
+        assert!(DebugOptions::from_args(["--watch", "d"].map(String::from).into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let program = vec![2, 4, 1, 5, 7, 5, 0, 3, 4, 1, 1, 6, 5, 5, 3, 0];
+        assert_eq!(
+            disassemble(&program),
+            [
+                "L0:",
+                "    bst A",
+                "    bxl 5",
+                "    cdv B",
+                "    adv 3",
+                "    bxc 1",
+                "    bxl 6",
+                "    out B",
+                "    jnz L0",
+                ""
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_assemble() {
+        let text = "start:\n    adv 1 ; A >>= 1\n    out A\n    jnz start\n    bxc\n";
+        assert_eq!(assemble(text), Ok(vec![0, 1, 5, 4, 3, 0, 4, 0]));
+        assert!(assemble("    jmp 0\n").is_err());
+        assert!(assemble("    bxl 8\n").is_err());
+        assert!(assemble("    out\n").is_err());
+        let far_label = format!("{}far:\n    jnz far\n", "    bxl 1\n".repeat(4));
+        assert_eq!(
+            assemble(&far_label),
+            Err("Line 6: label far is at address 8, out of reach of a 3-bit jump".to_string())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let programs = vec![
+            vec![0, 1, 5, 4, 3, 0],
+            vec![2, 4, 1, 5, 7, 5, 0, 3, 4, 1, 1, 6, 5, 5, 3, 0],
+            vec![0, 3, 5, 4, 3, 0],
+            vec![3, 3, 5, 7, 8, 2, 4, 0, 6],
+        ];
+        for program in programs {
+            assert_eq!(assemble(&disassemble(&program)), Ok(program));
+        }
+    }
 }
@@ -1,18 +1,21 @@
 use super::computer::Computer;
 
 use nom::{
-    bytes::complete::tag, character::complete::u32, multi::separated_list1, sequence::tuple,
+    bytes::complete::tag,
+    character::complete::{u32, u64},
+    multi::separated_list1,
+    sequence::tuple,
     IResult,
 };
 
 pub fn read_input(input: &str) -> IResult<&str, Computer> {
     let (input, (_, a_register, _, b_register, _, c_register, _, program)) = tuple((
         tag("Register A: "),
-        u32,
+        u64,
         tag("\nRegister B: "),
-        u32,
+        u64,
         tag("\nRegister C: "),
-        u32,
+        u64,
         tag("\n\nProgram: "),
         separated_list1(tag(","), u32),
     ))(input)?;
@@ -2,48 +2,150 @@ mod parser;
 mod ram;
 
 use parser::read_input;
-use ram::Ram;
+use ram::{Blockage, Ram};
 use std::fs;
 
-fn get_result_part1(ram: &Ram) -> u32 {
+struct Options {
+    input_filename: String,
+    size: usize,
+    fallen_bytes: usize,
+}
+
+impl Options {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut input_filename = None;
+        let mut size = 70;
+        let mut fallen_bytes = 1024;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--size" => size = Self::parse_value(&arg, args.next())?,
+                "--bytes" => fallen_bytes = Self::parse_value(&arg, args.next())?,
+                _ if input_filename.is_none() => input_filename = Some(arg),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+        let input_filename =
+            input_filename.ok_or("Please, provide the input file as argument".to_string())?;
+        Ok(Options {
+            input_filename,
+            size,
+            fallen_bytes,
+        })
+    }
+
+    fn parse_value(option: &str, value: Option<String>) -> Result<usize, String> {
+        value
+            .and_then(|value| value.parse().ok())
+            .ok_or(format!("Option {} expects a non-negative integer", option))
+    }
+}
+
+fn get_result_part1(ram: &Ram) -> Option<u32> {
     ram.clone().run()
 }
 
+fn get_result_part2(ram: &Ram) -> Blockage {
+    ram.first_blocking_byte()
+}
+
 fn main() {
-    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Please, provide the input file as argument");
+    let options = Options::from_args(std::env::args().skip(1)).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        eprintln!("Usage: day_18 <input> [--size 70] [--bytes 1024]");
         std::process::exit(1)
     });
     let ram_string =
-        fs::read_to_string(input_filename).expect("Should have been able to read the file");
-    // TODO: Decouple functionality from the number of limiting bytes of the parser. It should be the responsibility of the Ram class.
-    let (_, ram) = read_input(&ram_string, 70, 1024).unwrap();
-    let result_part1 = get_result_part1(&ram);
-    println!("Result part 1: {}", result_part1);
-
-    let mut result_part2 = 0;
-    // TODO: Add binary search
-    for i in 0..=3450 {
-        let (_, ram) = read_input(&ram_string, 70, i).unwrap();
-        let result = get_result_part1(&ram);
-        if result == 0 {
-            result_part2 = i;
-            break;
-        }
+        fs::read_to_string(options.input_filename).expect("Should have been able to read the file");
+    let (_, bytes) = read_input(&ram_string).unwrap();
+    let mut ram = Ram::new(bytes, options.size);
+    ram.drop_bytes(options.fallen_bytes);
+
+    match get_result_part1(&ram) {
+        Some(steps) => println!("Result part 1: {}", steps),
+        None => println!("Result part 1: the exit cannot be reached"),
+    }
+
+    match get_result_part2(&ram) {
+        Blockage::Byte(position) => println!("Result part 2: {}", position),
+        Blockage::BeforeAnyByte => println!("Result part 2: the exit is blocked from the start"),
+        Blockage::Never => println!("Result part 2: the exit is never blocked"),
     }
-    println!("Result part 2: {}", result_part2);
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use ram::Position;
 
-    #[test]
-    fn example_part1() {
+    fn read_example() -> Ram {
         let ram_string =
             fs::read_to_string("example.txt").expect("Should have been able to read the file");
-        let (_, ram) = read_input(&ram_string, 6, 12).unwrap();
+        let (_, bytes) = read_input(&ram_string).unwrap();
+        Ram::new(bytes, 6)
+    }
+
+    #[test]
+    fn example_part1() {
+        let mut ram = read_example();
+        ram.drop_bytes(12);
         let result = get_result_part1(&ram);
-        assert_eq!(result, 22);
+        assert_eq!(result, Some(22));
+    }
+
+    #[test]
+    fn example_part2() {
+        let ram = read_example();
+        let result = get_result_part2(&ram);
+        assert_eq!(result, Blockage::Byte(Position::new((6, 1))));
+    }
+
+    #[test]
+    fn test_time_of() {
+        let ram = read_example();
+        assert_eq!(ram.time_of(Position::new((5, 4))), Some(0));
+        assert_eq!(ram.time_of(Position::new((6, 1))), Some(20));
+        assert_eq!(ram.time_of(Position::new((0, 0))), None);
+        assert_eq!(ram.time_of(Position::new((7, 7))), None);
+    }
+
+    #[test]
+    fn test_drop_bytes() {
+        let mut ram = read_example();
+        ram.drop_bytes(20);
+        assert_eq!(ram.clone().run(), ram.at_time(20).run());
+        ram.drop_bytes(1);
+        assert_eq!(ram.clone().run(), None);
+        ram.drop_bytes(100);
+        assert_eq!(ram, ram.at_time(25));
+    }
+
+    #[test]
+    fn test_start_is_end() {
+        let ram = Ram::new(vec![Position::new((0, 1)), Position::new((1, 0))], 0);
+        assert_eq!(ram.clone().run(), Some(0));
+        assert_eq!(get_result_part2(&ram), Blockage::Never);
+    }
+
+    #[test]
+    fn test_cell_at() {
+        let ram = read_example();
+        let position = Position::new((5, 4));
+        assert_eq!(ram.cell_at(position, 0), Some(ram::Cell::Empty));
+        assert_eq!(ram.cell_at(position, 1), Some(ram::Cell::Wall));
+        assert_eq!(ram.cell_at(Position::new((7, 0)), 1), None);
+    }
+
+    #[test]
+    fn test_options() {
+        let args = ["example.txt", "--size", "6", "--bytes", "12"].map(String::from);
+        let options = Options::from_args(args.into_iter()).unwrap();
+        assert_eq!(options.input_filename, "example.txt");
+        assert_eq!((options.size, options.fallen_bytes), (6, 12));
+        assert!(Options::from_args(["--size".to_string()].into_iter()).is_err());
+        let args = ["example.txt", "--size", "0", "--bytes", "-1"].map(String::from);
+        assert_eq!(
+            Options::from_args(args.into_iter()).err(),
+            Some("Option --bytes expects a non-negative integer".to_string())
+        );
     }
 }
@@ -4,39 +4,87 @@ use nom::lib::std::fmt;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Ram {
-    map: Vec<Vec<Cell>>,
+    bytes: Vec<Position>,
+    fall_times: Vec<Vec<Option<usize>>>,
+    fallen_bytes: usize,
     start: Position,
     end: Position,
     size: usize,
 }
 
 impl Ram {
-    pub fn new(bytes: Vec<Position>, size: usize, fallen_bytes: usize) -> Self {
-        let bytes = &bytes[..fallen_bytes];
-        let mut map = vec![];
-        for y in 0..=size {
-            map.push(vec![]);
-            for x in 0..=size {
-                let position = Position::new((x, y));
-                let cell = if bytes.contains(&position) {
-                    Cell::Wall
-                } else {
-                    Cell::Empty
-                };
-                map[y].push(cell);
+    pub fn new(bytes: Vec<Position>, size: usize) -> Self {
+        let mut fall_times = vec![vec![None; size + 1]; size + 1];
+        for (time, byte) in bytes.iter().enumerate() {
+            if byte.x <= size && byte.y <= size {
+                fall_times[byte.y][byte.x].get_or_insert(time);
             }
         }
         let start = Position { x: 0, y: 0 };
         let end = Position { x: size, y: size };
         Ram {
-            map,
+            bytes,
+            fall_times,
+            fallen_bytes: 0,
             start,
             end,
             size,
         }
     }
 
-    pub fn run(&mut self) -> u32 {
+    pub fn drop_bytes(&mut self, n: usize) {
+        self.fallen_bytes = (self.fallen_bytes + n).min(self.bytes.len());
+    }
+
+    pub fn at_time(&self, time: usize) -> Self {
+        Ram {
+            fallen_bytes: time.min(self.bytes.len()),
+            ..self.clone()
+        }
+    }
+
+    pub fn time_of(&self, position: Position) -> Option<usize> {
+        self.fall_times
+            .get(position.y)
+            .and_then(|row| row.get(position.x))
+            .copied()
+            .flatten()
+    }
+
+    pub fn cell_at(&self, position: Position, time: usize) -> Option<Cell> {
+        if position.x > self.size || position.y > self.size {
+            return None;
+        }
+        match self.time_of(position) {
+            Some(fall_time) if fall_time < time => Some(Cell::Wall),
+            _ => Some(Cell::Empty),
+        }
+    }
+
+    pub fn first_blocking_byte(&self) -> Blockage {
+        let is_blocked = |time: usize| self.at_time(time).run().is_none();
+        if !is_blocked(self.bytes.len()) {
+            return Blockage::Never;
+        }
+
+        let (mut low, mut high) = (0, self.bytes.len());
+        while low < high {
+            let middle = (low + high) / 2;
+            if is_blocked(middle) {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        match low.checked_sub(1) {
+            Some(index) => Blockage::Byte(self.bytes[index]),
+            None => Blockage::BeforeAnyByte,
+        }
+    }
+
+    // Length of the shortest path from the start to the end, or None when
+    // the fallen bytes cut the end off.
+    pub fn run(&mut self) -> Option<u32> {
         let mut frontier: HashSet<Position> = HashSet::new();
         frontier.insert(self.start);
         let mut g_score: HashMap<Position, u32> = HashMap::new();
@@ -44,14 +92,14 @@ impl Ram {
         let mut f_score: HashMap<Position, u32> = HashMap::new();
         f_score.insert(self.start, self.heuristic(self.start));
 
-        while frontier.len() > 0 {
+        while !frontier.is_empty() {
             let current_position = frontier
                 .clone()
                 .into_iter()
                 .min_by_key(|candidate| f_score[candidate])
                 .unwrap();
             if current_position == self.end {
-                return f_score[&current_position];
+                return Some(f_score[&current_position]);
             }
             frontier.remove(&current_position);
             for neighbour in self.get_neighbours(current_position) {
@@ -64,7 +112,7 @@ impl Ram {
             }
         }
 
-        0
+        None
     }
 
     fn heuristic(&self, position: Position) -> u32 {
@@ -88,31 +136,39 @@ impl Ram {
     }
 
     fn get_cell(&self, (x, y): (i32, i32)) -> Option<Cell> {
-        if 0 <= x && x <= self.size as i32 && 0 <= y && y <= self.size as i32 {
-            Some(self.map[y as usize][x as usize])
+        if 0 <= x && 0 <= y {
+            self.cell_at(Position::new((x as usize, y as usize)), self.fallen_bytes)
         } else {
             None
         }
     }
 
     pub fn _write_map(&self, marked_position: &Position) {
-        self.map.iter().enumerate().for_each(|(y, row)| {
-            row.iter().enumerate().for_each(|(x, cell)| {
-                if marked_position == (&Position { x, y }) {
+        for y in 0..=self.size {
+            for x in 0..=self.size {
+                let position = Position { x, y };
+                if marked_position == &position {
                     print!("X");
-                } else if (self.start == Position { x, y }) {
+                } else if self.start == position {
                     print!("S");
-                } else if (self.end == Position { x, y }) {
+                } else if self.end == position {
                     print!("E");
                 } else {
-                    print!("{}", cell);
+                    print!("{}", self.cell_at(position, self.fallen_bytes).unwrap());
                 }
-            });
-            print!("\n");
-        });
+            }
+            println!();
+        }
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Blockage {
+    Never,
+    BeforeAnyByte,
+    Byte(Position),
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum Cell {
     Wall,
@@ -140,3 +196,9 @@ impl Position {
         Position { x, y }
     }
 }
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
@@ -0,0 +1,156 @@
+use std::{collections::HashMap, iter};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Keypad {
+    keys: HashMap<char, Position>,
+    gap: Position,
+}
+
+impl Keypad {
+    pub fn new(layout: &[&str]) -> Self {
+        let mut keys = HashMap::new();
+        let mut gap = None;
+        for (y, row) in layout.iter().enumerate() {
+            for (x, key) in row.chars().enumerate() {
+                let position = Position {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                if key == ' ' {
+                    gap = Some(position);
+                } else {
+                    keys.insert(key, position);
+                }
+            }
+        }
+        Keypad {
+            keys,
+            gap: gap.expect("A keypad layout should have a gap"),
+        }
+    }
+
+    pub fn numeric() -> Self {
+        Keypad::new(&["789", "456", "123", " 0A"])
+    }
+
+    pub fn directional() -> Self {
+        Keypad::new(&[" ^A", "<v>"])
+    }
+
+    pub fn has_key(&self, key: char) -> bool {
+        self.keys.contains_key(&key)
+    }
+
+    pub fn get_paths(&self, from: char, to: char) -> Vec<String> {
+        let start = self.keys[&from];
+        let end = self.keys[&to];
+        let horizontal = if end.x > start.x { '>' } else { '<' };
+        let vertical = if end.y > start.y { 'v' } else { '^' };
+        let horizontal_moves = iter::repeat_n(horizontal, start.x.abs_diff(end.x) as usize);
+        let vertical_moves = iter::repeat_n(vertical, start.y.abs_diff(end.y) as usize);
+        let horizontal_corner = Position {
+            x: end.x,
+            y: start.y,
+        };
+        let vertical_corner = Position {
+            x: start.x,
+            y: end.y,
+        };
+
+        let mut paths = vec![];
+        if horizontal_corner != self.gap {
+            let path: String = horizontal_moves
+                .clone()
+                .chain(vertical_moves.clone())
+                .chain(iter::once('A'))
+                .collect();
+            paths.push(path);
+        }
+        if vertical_corner != self.gap {
+            let path: String = vertical_moves
+                .chain(horizontal_moves)
+                .chain(iter::once('A'))
+                .collect();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeypadChain {
+    numeric: Keypad,
+    directional: Keypad,
+    robots: usize,
+    cache: HashMap<(char, char, usize), u64>,
+}
+
+impl KeypadChain {
+    pub fn new(robots: usize) -> Self {
+        KeypadChain {
+            numeric: Keypad::numeric(),
+            directional: Keypad::directional(),
+            robots,
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn get_presses(&mut self, code: &str) -> u64 {
+        let mut presses = 0;
+        let mut current = 'A';
+        for key in code.chars() {
+            presses += self
+                .numeric
+                .get_paths(current, key)
+                .into_iter()
+                .map(|path| self.get_sequence_cost(&path, self.robots))
+                .min()
+                .unwrap();
+            current = key;
+        }
+        presses
+    }
+
+    pub fn get_complexity(&mut self, code: &str) -> u64 {
+        let numeric_part: u64 = code
+            .trim_start_matches('0')
+            .trim_end_matches('A')
+            .parse()
+            .unwrap_or(0);
+        self.get_presses(code) * numeric_part
+    }
+
+    fn get_sequence_cost(&mut self, sequence: &str, depth: usize) -> u64 {
+        iter::once('A')
+            .chain(sequence.chars())
+            .zip(sequence.chars())
+            .map(|(from, to)| self.get_move_cost(from, to, depth))
+            .sum()
+    }
+
+    fn get_move_cost(&mut self, from: char, to: char, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if let Some(&cost) = self.cache.get(&(from, to, depth)) {
+            return cost;
+        }
+        let cost = self
+            .directional
+            .get_paths(from, to)
+            .into_iter()
+            .map(|path| self.get_sequence_cost(&path, depth - 1))
+            .min()
+            .unwrap();
+        self.cache.insert((from, to, depth), cost);
+        cost
+    }
+}
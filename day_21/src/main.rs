@@ -0,0 +1,100 @@
+mod keypad;
+mod parser;
+
+use keypad::KeypadChain;
+use parser::read_input;
+use std::fs;
+
+fn get_result(codes: &[&str], robots: usize) -> u64 {
+    let mut chain = KeypadChain::new(robots);
+    codes.iter().map(|code| chain.get_complexity(code)).sum()
+}
+
+fn read_codes(codes_string: &str) -> Result<Vec<&str>, String> {
+    match read_input(codes_string) {
+        Ok(("", codes)) => Ok(codes),
+        Ok((rest, _)) => Err(format!(
+            "Invalid code: {}",
+            rest.lines().next().unwrap_or_default()
+        )),
+        Err(_) => Err(format!(
+            "Invalid code: {}",
+            codes_string.lines().next().unwrap_or_default()
+        )),
+    }
+}
+
+fn main() {
+    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("Please, provide the input file as argument");
+        std::process::exit(1)
+    });
+    let codes_string =
+        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let codes = read_codes(&codes_string).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(1)
+    });
+
+    let result_part1 = get_result(&codes, 2);
+    println!("Result part 1: {}", result_part1);
+
+    let result_part2 = get_result(&codes, 25);
+    println!("Result part 2: {}", result_part2);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use keypad::Keypad;
+
+    #[test]
+    fn test_paths() {
+        let numeric = Keypad::numeric();
+        assert_eq!(numeric.get_paths('A', '0'), vec!["<A"]);
+        assert_eq!(numeric.get_paths('A', '1'), vec!["^<<A"]);
+        assert_eq!(numeric.get_paths('7', '0'), vec![">vvvA"]);
+        assert_eq!(numeric.get_paths('2', '9'), vec![">^^A", "^^>A"]);
+        assert_eq!(numeric.get_paths('5', '5'), vec!["A"]);
+
+        let directional = Keypad::directional();
+        assert_eq!(directional.get_paths('A', '<'), vec!["v<<A"]);
+        assert_eq!(directional.get_paths('<', '^'), vec![">^A"]);
+    }
+
+    #[test]
+    fn test_presses() {
+        assert_eq!(KeypadChain::new(0).get_presses("029A"), 12);
+        assert_eq!(KeypadChain::new(1).get_presses("029A"), 28);
+        assert_eq!(KeypadChain::new(2).get_presses("029A"), 68);
+        assert_eq!(KeypadChain::new(2).get_presses("379A"), 64);
+    }
+
+    #[test]
+    fn example_part1() {
+        let codes_string =
+            fs::read_to_string("example.txt").expect("Should have been able to read the file");
+        let (_, codes) = read_input(&codes_string).unwrap();
+        let result = get_result(&codes, 2);
+        assert_eq!(result, 126384);
+    }
+
+    #[test]
+    fn example_part2() {
+        let codes_string =
+            fs::read_to_string("example.txt").expect("Should have been able to read the file");
+        let (_, codes) = read_input(&codes_string).unwrap();
+        let result = get_result(&codes, 25);
+        assert_eq!(result, 154115708116294);
+    }
+
+    #[test]
+    fn invalid_codes() {
+        assert_eq!(read_codes("029A\n980A\n"), Ok(vec!["029A", "980A"]));
+        assert_eq!(
+            read_codes("029A\n12Z\n"),
+            Err("Invalid code: 12Z".to_string())
+        );
+        assert_eq!(read_codes("12Z\n"), Err("Invalid code: 12Z".to_string()));
+    }
+}
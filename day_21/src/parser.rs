@@ -0,0 +1,36 @@
+use super::keypad::Keypad;
+
+use nom::{
+    character::complete::{alphanumeric1, newline},
+    combinator::verify,
+    multi::many1,
+    sequence::terminated,
+    IResult,
+};
+
+// Codes are typed on the numeric keypad, so they may only use its keys.
+pub fn read_input(input: &str) -> IResult<&str, Vec<&str>> {
+    let numeric = Keypad::numeric();
+    let codes = many1(terminated(
+        verify(alphanumeric1, |code: &str| {
+            code.chars().all(|key| numeric.has_key(key))
+        }),
+        newline,
+    ))(input);
+    codes
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        assert_eq!(
+            read_input(&format!("{}\n{}\n{}\n", "029A", "980A", "179A")),
+            Ok(("", vec!["029A", "980A", "179A"]))
+        );
+        assert_eq!(read_input("029A\n12Z\n"), Ok(("12Z\n", vec!["029A"])));
+        assert!(read_input("12Z\n").is_err());
+    }
+}
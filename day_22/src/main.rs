@@ -46,6 +46,63 @@ fn get_result_part1(market: Vec<u64>) -> u64 {
         .sum()
 }
 
+const CHANGES_WINDOW: usize = 4;
+const CHANGE_VALUES: usize = 19;
+
+fn get_prices(secret_number: u64, iterations: usize) -> Vec<i8> {
+    let mut prices = vec![(secret_number % 10) as i8];
+    (0..iterations).fold(secret_number, |acc, _| {
+        let next = iteration(acc);
+        prices.push((next % 10) as i8);
+        next
+    });
+    prices
+}
+
+fn get_changes(prices: &[i8]) -> Vec<i8> {
+    prices.windows(2).map(|pair| pair[1] - pair[0]).collect()
+}
+
+fn encode_sequence(changes: &[i8]) -> usize {
+    changes.iter().fold(0, |acc, &change| {
+        acc * CHANGE_VALUES + (change + 9) as usize
+    })
+}
+
+fn decode_sequence(mut index: usize) -> [i8; CHANGES_WINDOW] {
+    let mut sequence = [0; CHANGES_WINDOW];
+    for change in sequence.iter_mut().rev() {
+        *change = (index % CHANGE_VALUES) as i8 - 9;
+        index /= CHANGE_VALUES;
+    }
+    sequence
+}
+
+fn get_result_part2(market: Vec<u64>) -> (u64, [i8; CHANGES_WINDOW]) {
+    let sequences = CHANGE_VALUES.pow(CHANGES_WINDOW as u32);
+    let mut bananas = vec![0_u64; sequences];
+    let mut last_seen_by = vec![usize::MAX; sequences];
+
+    for (buyer, secret_number) in market.into_iter().enumerate() {
+        let prices = get_prices(secret_number, 2000);
+        let changes = get_changes(&prices);
+        for (i, window) in changes.windows(CHANGES_WINDOW).enumerate() {
+            let index = encode_sequence(window);
+            if last_seen_by[index] != buyer {
+                last_seen_by[index] = buyer;
+                bananas[index] += prices[i + CHANGES_WINDOW] as u64;
+            }
+        }
+    }
+
+    let (best_index, &best_bananas) = bananas
+        .iter()
+        .enumerate()
+        .max_by_key(|&(_, total)| total)
+        .unwrap();
+    (best_bananas, decode_sequence(best_index))
+}
+
 pub fn main() {
     let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
         eprintln!("Please, provide the input file as argument");
@@ -55,9 +112,11 @@ pub fn main() {
         fs::read_to_string(input_filename).expect("Should have been able to read the file");
 
     let (_, market) = read_input(&market_string).unwrap();
-    let result_part1 = get_result_part1(market);
-
+    let result_part1 = get_result_part1(market.clone());
     println!("Result part 1: {}", result_part1);
+
+    let (result_part2, sequence) = get_result_part2(market);
+    println!("Result part 2: {} (sequence {:?})", result_part2, sequence);
 }
 
 #[cfg(test)]
@@ -99,4 +158,26 @@ pub mod tests {
         let result = get_result_part1(market);
         assert_eq!(result, 37327623);
     }
+
+    #[test]
+    fn test_easy_part2() {
+        let prices = get_prices(123, 9);
+        assert_eq!(prices, vec![3, 0, 6, 5, 4, 4, 6, 4, 4, 2]);
+        assert_eq!(get_changes(&prices), vec![-3, 6, -1, -1, 0, 2, -2, 0, -2]);
+    }
+
+    #[test]
+    fn test_sequence_encoding() {
+        let sequence = [-2, 1, -1, 3];
+        assert_eq!(decode_sequence(encode_sequence(&sequence)), sequence);
+        assert_eq!(encode_sequence(&[-9, -9, -9, -9]), 0);
+        assert_eq!(encode_sequence(&[9, 9, 9, 9]), 19_usize.pow(4) - 1);
+    }
+
+    #[test]
+    fn test_hard_part2() {
+        let market = vec![1, 2, 3, 2024];
+        let result = get_result_part2(market);
+        assert_eq!(result, (23, [-2, 1, -1, 3]));
+    }
 }
@@ -0,0 +1,115 @@
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Graph {
+    adjacency: HashMap<String, BTreeSet<String>>,
+}
+
+impl Graph {
+    pub fn new(edges: &[(&str, &str)]) -> Self {
+        let mut graph = Graph::default();
+        edges.iter().for_each(|(from, to)| graph.add_edge(from, to));
+        graph
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.adjacency
+            .entry(from.to_string())
+            .or_default()
+            .insert(to.to_string());
+        self.adjacency
+            .entry(to.to_string())
+            .or_default()
+            .insert(from.to_string());
+    }
+
+    pub fn nodes(&self) -> BTreeSet<&String> {
+        self.adjacency.keys().collect()
+    }
+
+    pub fn neighbours(&self, node: &str) -> Option<&BTreeSet<String>> {
+        self.adjacency.get(node)
+    }
+
+    pub fn degree(&self, node: &str) -> usize {
+        self.neighbours(node)
+            .map_or(0, |neighbours| neighbours.len())
+    }
+
+    pub fn are_connected(&self, from: &str, to: &str) -> bool {
+        self.neighbours(from)
+            .is_some_and(|neighbours| neighbours.contains(to))
+    }
+
+    pub fn get_triangles(&self) -> Vec<[&String; 3]> {
+        let mut triangles = vec![];
+        for (first, neighbours) in self.adjacency.iter() {
+            for second in neighbours.iter().filter(|&second| second > first) {
+                for third in neighbours.iter().filter(|&third| third > second) {
+                    if self.are_connected(second, third) {
+                        triangles.push([first, second, third]);
+                    }
+                }
+            }
+        }
+        triangles.sort();
+        triangles
+    }
+
+    pub fn get_maximal_cliques(&self) -> Vec<BTreeSet<&String>> {
+        let mut cliques = vec![];
+        self.bron_kerbosch(BTreeSet::new(), self.nodes(), BTreeSet::new(), &mut cliques);
+        cliques
+    }
+
+    pub fn get_maximum_clique(&self) -> BTreeSet<&String> {
+        self.get_maximal_cliques()
+            .into_iter()
+            .max_by_key(|clique| clique.len())
+            .unwrap_or_default()
+    }
+
+    fn bron_kerbosch<'a>(
+        &'a self,
+        clique: BTreeSet<&'a String>,
+        mut candidates: BTreeSet<&'a String>,
+        mut excluded: BTreeSet<&'a String>,
+        cliques: &mut Vec<BTreeSet<&'a String>>,
+    ) {
+        if candidates.is_empty() && excluded.is_empty() {
+            cliques.push(clique);
+            return;
+        }
+
+        let pivot = candidates
+            .union(&excluded)
+            .max_by_key(|node| self.degree(node))
+            .copied()
+            .unwrap();
+        let pivot_neighbours = &self.adjacency[pivot];
+        let branches: Vec<&String> = candidates
+            .iter()
+            .filter(|node| !pivot_neighbours.contains(**node))
+            .copied()
+            .collect();
+
+        for node in branches {
+            let neighbours = &self.adjacency[node];
+            let mut next_clique = clique.clone();
+            next_clique.insert(node);
+            let next_candidates = candidates
+                .iter()
+                .filter(|candidate| neighbours.contains(**candidate))
+                .copied()
+                .collect();
+            let next_excluded = excluded
+                .iter()
+                .filter(|candidate| neighbours.contains(**candidate))
+                .copied()
+                .collect();
+            self.bron_kerbosch(next_clique, next_candidates, next_excluded, cliques);
+            candidates.remove(node);
+            excluded.insert(node);
+        }
+    }
+}
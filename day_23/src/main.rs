@@ -0,0 +1,77 @@
+mod graph;
+mod parser;
+
+use graph::Graph;
+use parser::read_input;
+use std::fs;
+
+fn get_result_part1(graph: &Graph) -> usize {
+    graph
+        .get_triangles()
+        .into_iter()
+        .filter(|triangle| triangle.iter().any(|node| node.starts_with('t')))
+        .count()
+}
+
+fn get_result_part2(graph: &Graph) -> String {
+    graph
+        .get_maximum_clique()
+        .into_iter()
+        .map(|node| node.as_str())
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+fn main() {
+    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("Please, provide the input file as argument");
+        std::process::exit(1)
+    });
+    let network_string =
+        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let (_, edges) = read_input(&network_string).unwrap();
+    let graph = Graph::new(&edges);
+
+    let result_part1 = get_result_part1(&graph);
+    println!("Result part 1: {}", result_part1);
+
+    let result_part2 = get_result_part2(&graph);
+    println!("Result part 2: {}", result_part2);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn read_example() -> Graph {
+        let network_string =
+            fs::read_to_string("example.txt").expect("Should have been able to read the file");
+        let (_, edges) = read_input(&network_string).unwrap();
+        Graph::new(&edges)
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = read_example();
+        assert_eq!(graph.nodes().len(), 16);
+        assert_eq!(graph.degree("kh"), 4);
+        assert_eq!(graph.degree("zz"), 0);
+        assert!(graph.are_connected("tc", "kh"));
+        assert!(!graph.are_connected("tc", "de"));
+        assert_eq!(graph.get_triangles().len(), 12);
+    }
+
+    #[test]
+    fn example_part1() {
+        let graph = read_example();
+        let result = get_result_part1(&graph);
+        assert_eq!(result, 7);
+    }
+
+    #[test]
+    fn example_part2() {
+        let graph = read_example();
+        let result = get_result_part2(&graph);
+        assert_eq!(result, "co,de,ka,ta");
+    }
+}
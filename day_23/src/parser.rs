@@ -0,0 +1,27 @@
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, newline},
+    multi::many1,
+    sequence::{separated_pair, terminated},
+    IResult,
+};
+
+pub fn read_input(input: &str) -> IResult<&str, Vec<(&str, &str)>> {
+    many1(terminated(
+        separated_pair(alphanumeric1, tag("-"), alphanumeric1),
+        newline,
+    ))(input)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        assert_eq!(
+            read_input(&format!("{}\n{}\n{}\n", "kh-tc", "qp-kh", "de-cg")),
+            Ok(("", vec![("kh", "tc"), ("qp", "kh"), ("de", "cg")]))
+        );
+    }
+}
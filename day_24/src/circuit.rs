@@ -0,0 +1,201 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use nom::lib::std::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum Operation {
+    And,
+    Or,
+    Xor,
+}
+
+impl Operation {
+    pub fn new(operation: &str) -> Option<Self> {
+        match operation {
+            "AND" => Some(Operation::And),
+            "OR" => Some(Operation::Or),
+            "XOR" => Some(Operation::Xor),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, left: bool, right: bool) -> bool {
+        match self {
+            Operation::And => left && right,
+            Operation::Or => left || right,
+            Operation::Xor => left ^ right,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Xor => "XOR",
+        };
+        write!(f, "{}", operation)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Hash)]
+pub struct Gate {
+    pub left: String,
+    pub operation: Operation,
+    pub right: String,
+    pub output: String,
+}
+
+impl Gate {
+    fn has_input(&self, wire: &str) -> bool {
+        self.left == wire || self.right == wire
+    }
+
+    fn has_first_bit_inputs(&self) -> bool {
+        self.has_input("x00") && self.has_input("y00")
+    }
+
+    fn has_primary_inputs(&self) -> bool {
+        [&self.left, &self.right]
+            .iter()
+            .all(|wire| wire.starts_with('x') || wire.starts_with('y'))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Circuit {
+    pub initial_values: HashMap<String, bool>,
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    pub fn new(initial_values: HashMap<String, bool>, gates: Vec<Gate>) -> Self {
+        Circuit {
+            initial_values,
+            gates,
+        }
+    }
+
+    pub fn get_topological_order(&self) -> Option<Vec<&Gate>> {
+        let mut pending_inputs: Vec<usize> = self
+            .gates
+            .iter()
+            .map(|gate| {
+                [&gate.left, &gate.right]
+                    .into_iter()
+                    .filter(|wire| !self.initial_values.contains_key(*wire))
+                    .count()
+            })
+            .collect();
+        let mut consumers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, gate) in self.gates.iter().enumerate() {
+            for wire in [&gate.left, &gate.right] {
+                consumers.entry(wire).or_default().push(index);
+            }
+        }
+
+        let mut ready: VecDeque<usize> = (0..self.gates.len())
+            .filter(|&index| pending_inputs[index] == 0)
+            .collect();
+        let mut order = vec![];
+        while let Some(index) = ready.pop_front() {
+            let gate = &self.gates[index];
+            order.push(gate);
+            for &consumer in consumers.get(gate.output.as_str()).unwrap_or(&vec![]) {
+                pending_inputs[consumer] -= 1;
+                if pending_inputs[consumer] == 0 {
+                    ready.push_back(consumer);
+                }
+            }
+        }
+
+        if order.len() == self.gates.len() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    pub fn evaluate(&self) -> Option<HashMap<String, bool>> {
+        let mut values = self.initial_values.clone();
+        for gate in self.get_topological_order()? {
+            let value = gate
+                .operation
+                .apply(*values.get(&gate.left)?, *values.get(&gate.right)?);
+            values.insert(gate.output.clone(), value);
+        }
+        Some(values)
+    }
+
+    pub fn get_number(values: &HashMap<String, bool>, prefix: char) -> u64 {
+        let mut bits: Vec<(&String, &bool)> = values
+            .iter()
+            .filter(|(wire, _)| wire.starts_with(prefix))
+            .collect();
+        bits.sort();
+        bits.into_iter()
+            .rev()
+            .fold(0, |number, (_, &bit)| (number << 1) | bit as u64)
+    }
+
+    pub fn get_swapped_wires(&self) -> Vec<String> {
+        let last_output = self
+            .gates
+            .iter()
+            .map(|gate| &gate.output)
+            .filter(|wire| wire.starts_with('z'))
+            .max();
+        let feeds = |wire: &str, operation: Operation| {
+            self.gates
+                .iter()
+                .any(|gate| gate.operation == operation && gate.has_input(wire))
+        };
+
+        let mut swapped: BTreeSet<String> = BTreeSet::new();
+        for gate in self.gates.iter() {
+            let is_output = gate.output.starts_with('z');
+            let is_last_output = Some(&gate.output) == last_output;
+            let is_wrong = match gate.operation {
+                // Every sum bit is the XOR of the half sum and the carry, but
+                // the last bit is the carry out of the most significant adder.
+                _ if is_last_output => gate.operation != Operation::Or,
+                Operation::And | Operation::Or if is_output => true,
+                Operation::Xor if is_output => {
+                    gate.has_primary_inputs() && !gate.has_first_bit_inputs()
+                }
+                // An XOR not driving an output must be a half sum of x and y,
+                // which in turn feeds the XOR of the next sum bit.
+                Operation::Xor if !gate.has_primary_inputs() => true,
+                Operation::Xor => {
+                    !gate.has_first_bit_inputs() && !feeds(&gate.output, Operation::Xor)
+                }
+                // Carries are built by OR-ing two ANDs, except for the first
+                // bit, whose AND is directly the carry.
+                Operation::And => {
+                    !gate.has_first_bit_inputs() && !feeds(&gate.output, Operation::Or)
+                }
+                Operation::Or => false,
+            };
+            if is_wrong {
+                swapped.insert(gate.output.clone());
+            }
+        }
+        swapped.into_iter().collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n");
+        for (index, gate) in self.gates.iter().enumerate() {
+            dot.push_str(&format!(
+                "    gate{} [label=\"{}\", shape=box];\n",
+                index, gate.operation
+            ));
+            dot.push_str(&format!("    {} -> gate{};\n", gate.left, index));
+            dot.push_str(&format!("    {} -> gate{};\n", gate.right, index));
+            dot.push_str(&format!("    gate{} -> {};\n", index, gate.output));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
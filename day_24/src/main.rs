@@ -0,0 +1,166 @@
+mod circuit;
+mod parser;
+
+use circuit::Circuit;
+use parser::read_input;
+use std::fs;
+
+fn get_result_part1(circuit: &Circuit) -> Option<u64> {
+    let values = circuit.evaluate()?;
+    Some(Circuit::get_number(&values, 'z'))
+}
+
+fn get_result_part2(circuit: &Circuit) -> String {
+    circuit.get_swapped_wires().join(",")
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let input_filename = args.next().unwrap_or_else(|| {
+        eprintln!("Please, provide the input file as argument");
+        std::process::exit(1)
+    });
+    let dot_filename = match (args.next().as_deref(), args.next()) {
+        (Some("--dot"), Some(dot_filename)) => Some(dot_filename),
+        (None, _) => None,
+        _ => {
+            eprintln!("Usage: day_24 <input> [--dot <output.dot>]");
+            std::process::exit(1)
+        }
+    };
+    let circuit_string =
+        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let (_, circuit) = read_input(&circuit_string).unwrap();
+
+    if let Some(dot_filename) = dot_filename {
+        fs::write(dot_filename, circuit.to_dot()).expect("Should have been able to write the file");
+    }
+
+    match get_result_part1(&circuit) {
+        Some(result_part1) => println!("Result part 1: {}", result_part1),
+        None => println!("Result part 1: the circuit has a cycle or an unset wire"),
+    }
+
+    let result_part2 = get_result_part2(&circuit);
+    println!("Result part 2: {}", result_part2);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use circuit::{Gate, Operation};
+    use std::collections::HashMap;
+
+    fn gate(left: &str, operation: Operation, right: &str, output: &str) -> Gate {
+        Gate {
+            left: left.to_string(),
+            operation,
+            right: right.to_string(),
+            output: output.to_string(),
+        }
+    }
+
+    fn ripple_carry_adder(bits: usize, x: u64, y: u64) -> Circuit {
+        let mut initial_values = HashMap::new();
+        for bit in 0..bits {
+            initial_values.insert(format!("x{:02}", bit), (x >> bit) & 1 == 1);
+            initial_values.insert(format!("y{:02}", bit), (y >> bit) & 1 == 1);
+        }
+
+        let mut gates = vec![
+            gate("x00", Operation::Xor, "y00", "z00"),
+            gate("x00", Operation::And, "y00", "c00"),
+        ];
+        for bit in 1..bits {
+            let (x, y) = (format!("x{:02}", bit), format!("y{:02}", bit));
+            let carry = format!("c{:02}", bit - 1);
+            let next_carry = if bit == bits - 1 {
+                format!("z{:02}", bits)
+            } else {
+                format!("c{:02}", bit)
+            };
+            gates.push(gate(&x, Operation::Xor, &y, &format!("s{:02}", bit)));
+            gates.push(gate(&x, Operation::And, &y, &format!("a{:02}", bit)));
+            gates.push(gate(
+                &format!("s{:02}", bit),
+                Operation::Xor,
+                &carry,
+                &format!("z{:02}", bit),
+            ));
+            gates.push(gate(
+                &format!("s{:02}", bit),
+                Operation::And,
+                &carry,
+                &format!("b{:02}", bit),
+            ));
+            gates.push(gate(
+                &format!("a{:02}", bit),
+                Operation::Or,
+                &format!("b{:02}", bit),
+                &next_carry,
+            ));
+        }
+        Circuit::new(initial_values, gates)
+    }
+
+    fn swap_outputs(circuit: &mut Circuit, first: &str, second: &str) {
+        for gate in circuit.gates.iter_mut() {
+            if gate.output == first {
+                gate.output = second.to_string();
+            } else if gate.output == second {
+                gate.output = first.to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn example_part1() {
+        let circuit_string =
+            fs::read_to_string("example.txt").expect("Should have been able to read the file");
+        let (_, circuit) = read_input(&circuit_string).unwrap();
+        let result = get_result_part1(&circuit);
+        assert_eq!(result, Some(4));
+    }
+
+    #[test]
+    fn test_adder() {
+        let circuit = ripple_carry_adder(8, 173, 94);
+        assert_eq!(get_result_part1(&circuit), Some(173 + 94));
+        assert_eq!(get_result_part2(&circuit), "");
+    }
+
+    #[test]
+    fn test_swapped_adder() {
+        let mut circuit = ripple_carry_adder(8, 173, 94);
+        swap_outputs(&mut circuit, "z03", "c03");
+        swap_outputs(&mut circuit, "s05", "a05");
+        swap_outputs(&mut circuit, "z06", "b06");
+        swap_outputs(&mut circuit, "z02", "s02");
+        assert_eq!(
+            get_result_part2(&circuit),
+            "a05,b06,c03,s02,s05,z02,z03,z06"
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let circuit = Circuit::new(
+            HashMap::new(),
+            vec![
+                gate("a", Operation::And, "b", "c"),
+                gate("c", Operation::Or, "c", "b"),
+            ],
+        );
+        assert_eq!(get_result_part1(&circuit), None);
+    }
+
+    #[test]
+    fn test_dot() {
+        let circuit = ripple_carry_adder(2, 0, 0);
+        let dot = circuit.to_dot();
+        assert!(dot.starts_with("digraph circuit {\n"));
+        assert!(dot.contains("    gate0 [label=\"XOR\", shape=box];\n"));
+        assert!(dot.contains("    x00 -> gate0;\n"));
+        assert!(dot.contains("    gate6 -> z02;\n"));
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use super::circuit::{Circuit, Gate, Operation};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, newline, one_of},
+    combinator::map_opt,
+    multi::many1,
+    sequence::{separated_pair, terminated, tuple},
+    IResult,
+};
+
+pub fn read_input(input: &str) -> IResult<&str, Circuit> {
+    let (input, (initial_values, _, gates)) =
+        tuple((many1(read_initial_value), newline, many1(read_gate)))(input)?;
+    Ok((
+        input,
+        Circuit::new(initial_values.into_iter().collect::<HashMap<_, _>>(), gates),
+    ))
+}
+
+fn read_initial_value(input: &str) -> IResult<&str, (String, bool)> {
+    let (input, (wire, value)) = terminated(
+        separated_pair(alphanumeric1, tag(": "), one_of("01")),
+        newline,
+    )(input)?;
+    Ok((input, (wire.to_string(), value == '1')))
+}
+
+fn read_gate(input: &str) -> IResult<&str, Gate> {
+    let (input, (left, _, operation, _, right, _, output, _)) = tuple((
+        alphanumeric1,
+        tag(" "),
+        map_opt(alt((tag("AND"), tag("XOR"), tag("OR"))), Operation::new),
+        tag(" "),
+        alphanumeric1,
+        tag(" -> "),
+        alphanumeric1,
+        newline,
+    ))(input)?;
+    Ok((
+        input,
+        Gate {
+            left: left.to_string(),
+            operation,
+            right: right.to_string(),
+            output: output.to_string(),
+        },
+    ))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        assert_eq!(
+            read_input(&format!(
+                "{}\n{}\n\n{}\n{}\n",
+                "x00: 1", "y00: 0", "x00 AND y00 -> z00", "x00 XOR y00 -> z01"
+            )),
+            Ok((
+                "",
+                Circuit::new(
+                    [("x00".to_string(), true), ("y00".to_string(), false)]
+                        .into_iter()
+                        .collect(),
+                    vec![
+                        Gate {
+                            left: "x00".to_string(),
+                            operation: Operation::And,
+                            right: "y00".to_string(),
+                            output: "z00".to_string(),
+                        },
+                        Gate {
+                            left: "x00".to_string(),
+                            operation: Operation::Xor,
+                            right: "y00".to_string(),
+                            output: "z01".to_string(),
+                        }
+                    ]
+                )
+            ))
+        );
+    }
+}
@@ -0,0 +1,73 @@
+mod parser;
+mod schematic;
+
+use parser::read_input;
+use schematic::{Kind, Schematic};
+use std::fs;
+
+fn get_result(schematics: &[Schematic]) -> usize {
+    let (locks, keys): (Vec<&Schematic>, Vec<&Schematic>) = schematics
+        .iter()
+        .partition(|schematic| schematic.kind == Kind::Lock);
+    locks
+        .iter()
+        .map(|lock| keys.iter().filter(|key| lock.fits(key)).count())
+        .sum()
+}
+
+fn main() {
+    let input_filename = std::env::args().nth(1).unwrap_or_else(|| {
+        eprintln!("Please, provide the input file as argument");
+        std::process::exit(1)
+    });
+    let schematics_string =
+        fs::read_to_string(input_filename).expect("Should have been able to read the file");
+    let (_, schematics) = read_input(&schematics_string).unwrap();
+    let result = get_result(&schematics);
+    println!("Result: {}", result);
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn read_example() -> Vec<Schematic> {
+        let schematics_string =
+            fs::read_to_string("example.txt").expect("Should have been able to read the file");
+        let (_, schematics) = read_input(&schematics_string).unwrap();
+        schematics
+    }
+
+    #[test]
+    fn example_heights() {
+        let heights: Vec<(Kind, Vec<usize>)> = read_example()
+            .into_iter()
+            .map(|schematic| (schematic.kind, schematic.heights))
+            .collect();
+        assert_eq!(
+            heights,
+            vec![
+                (Kind::Lock, vec![0, 5, 3, 4, 3]),
+                (Kind::Lock, vec![1, 2, 0, 5, 3]),
+                (Kind::Key, vec![5, 0, 2, 1, 3]),
+                (Kind::Key, vec![4, 3, 4, 0, 2]),
+                (Kind::Key, vec![3, 0, 2, 0, 1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn example_fits() {
+        let schematics = read_example();
+        assert!(!schematics[0].fits(&schematics[2]));
+        assert!(schematics[0].fits(&schematics[4]));
+        assert!(!schematics[0].fits(&schematics[1]));
+    }
+
+    #[test]
+    fn example() {
+        let schematics = read_example();
+        let result = get_result(&schematics);
+        assert_eq!(result, 3);
+    }
+}
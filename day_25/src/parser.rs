@@ -0,0 +1,57 @@
+use super::schematic::Schematic;
+
+use nom::{
+    character::complete::{newline, one_of},
+    combinator::map_opt,
+    multi::{many1, separated_list1},
+    sequence::terminated,
+    IResult,
+};
+
+pub fn read_input(input: &str) -> IResult<&str, Vec<Schematic>> {
+    separated_list1(newline, read_schematic)(input)
+}
+
+fn read_schematic(input: &str) -> IResult<&str, Schematic> {
+    map_opt(many1(terminated(many1(one_of("#.")), newline)), |rows| {
+        Schematic::new(&rows)
+    })(input)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::schematic::Kind;
+
+    #[test]
+    fn test_parser() {
+        assert_eq!(
+            read_input(&format!(
+                "{}\n{}\n{}\n{}\n\n{}\n{}\n{}\n{}\n",
+                "###", ".#.", "...", "...", "...", "#..", "#.#", "###"
+            )),
+            Ok((
+                "",
+                vec![
+                    Schematic {
+                        kind: Kind::Lock,
+                        heights: vec![0, 1, 0],
+                        space: 2
+                    },
+                    Schematic {
+                        kind: Kind::Key,
+                        heights: vec![2, 0, 1],
+                        space: 2
+                    }
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parser_rejects_malformed_schematics() {
+        assert!(read_input("#####\n").is_err());
+        assert!(read_input("...\n#..\n##\n").is_err());
+        assert!(read_input("##\n.#.\n###\n").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Kind {
+    Lock,
+    Key,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Schematic {
+    pub kind: Kind,
+    pub heights: Vec<usize>,
+    pub space: usize,
+}
+
+impl Schematic {
+    pub fn new(rows: &[Vec<char>]) -> Option<Self> {
+        let first_row = rows.first()?;
+        let last_row = rows.last()?;
+        if rows.iter().any(|row| row.len() != first_row.len()) {
+            return None;
+        }
+        let kind = if first_row.iter().all(|&cell| cell == '#') {
+            Kind::Lock
+        } else if last_row.iter().all(|&cell| cell == '#') {
+            Kind::Key
+        } else {
+            return None;
+        };
+
+        let heights = (0..first_row.len())
+            .map(|column| {
+                rows.iter()
+                    .filter(|row| row.get(column) == Some(&'#'))
+                    .count()
+                    .checked_sub(1)
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        Some(Schematic {
+            kind,
+            heights,
+            space: rows.len().checked_sub(2)?,
+        })
+    }
+
+    pub fn fits(&self, other: &Schematic) -> bool {
+        self.kind != other.kind
+            && self.heights.len() == other.heights.len()
+            && self
+                .heights
+                .iter()
+                .zip(other.heights.iter())
+                .all(|(height, other_height)| height + other_height <= self.space)
+    }
+}